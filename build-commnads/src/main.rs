@@ -47,7 +47,7 @@ fn main() {
                 .collect();
 
             output.push_str(&format!(
-                "0x{code} => Recipe {{c: 0x{code}, name: \"{name}\", commands: Box::new([ ", // {cmds} ]) }},",
+                "0x{code} => Recipe {{c: 0x{code}, name: Cow::Borrowed(\"{name}\"), commands: Box::new([ ", // {cmds} ]) }},",
                 code = code.to_string(),
                 name = &name,
             ));
@@ -0,0 +1,219 @@
+//! Packing all glyphs into a single texture, for renderers drawing them
+//! straight from the GPU.
+
+use std::collections::BTreeMap;
+use std::ops::{AddAssign, SubAssign};
+
+use num::Float;
+
+use crate::font::Font;
+use crate::raster::Bitmap;
+
+/// Where a glyph is in the atlas.
+///
+/// The rectangle holds the inked part of the cell only; the bearing is its
+/// offset from the top left corner of the cell, in pixels.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct AtlasGlyph {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+    pub bearing: (usize, usize),
+}
+
+/// A grayscale texture with every glyph, and where to find each of them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Atlas {
+    pub cell_width: usize,
+    pub cell_height: usize,
+    pub bitmap: Bitmap,
+    pub glyphs: BTreeMap<u32, AtlasGlyph>,
+}
+
+/// Renders every glyph of a [`Font`] at one cell size into an [`Atlas`].
+///
+/// Glyphs are drawn with [`Font::render_cell`], cropped to their ink and
+/// packed in rows, tallest first. The result only depends on the `Font`,
+/// which hashes everything its glyphs depend on, and the settings, so it
+/// can be cached by them.
+pub struct AtlasBuilder<'a, F: Float> {
+    font: &'a Font<F>,
+    cell_width: usize,
+    cell_height: usize,
+    thickness: usize,
+    padding: usize,
+}
+
+impl<'a, F> AtlasBuilder<'a, F>
+where
+    F: Float + Default + AddAssign + SubAssign + 'static,
+{
+    /// An atlas builder for cells of the given size, with the stroke weight
+    /// of the font scaled to the cell and a pixel of padding between glyphs.
+    pub fn new(font: &'a Font<F>, cell_width: usize, cell_height: usize) -> AtlasBuilder<'a, F> {
+        AtlasBuilder {
+            font,
+            cell_width,
+            cell_height,
+            thickness: font.cell_thickness(cell_width),
+            padding: 1,
+        }
+    }
+
+    /// Width of light lines, in pixels.
+    pub fn thickness(mut self, thickness: usize) -> Self {
+        self.thickness = thickness;
+        self
+    }
+
+    /// Empty pixels around each glyph, so that texture filtering doesn't
+    /// bleed into neighbours.
+    pub fn padding(mut self, padding: usize) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    pub fn build(&self) -> Atlas {
+        let mut crops = Vec::new();
+        for c in self.font.code_points() {
+            let bitmap =
+                self.font
+                    .render_cell(c, self.cell_width, self.cell_height, self.thickness);
+            crops.push((c, crop(&bitmap)));
+        }
+        // tallest first makes for tight rows, ties keep code point order
+        crops.sort_by_key(|(c, (_, glyph))| (std::cmp::Reverse(glyph.height), *c));
+
+        let pad = self.padding;
+        let area: usize = crops
+            .iter()
+            .map(|(_, (_, g))| (g.width + pad) * (g.height + pad))
+            .sum();
+        let widest = crops.iter().map(|(_, (_, g))| g.width).max().unwrap_or(0);
+        let width = ((area as f64).sqrt().ceil() as usize)
+            .max(widest + 2 * pad)
+            .next_power_of_two();
+
+        // shelf packing, left to right and top to bottom
+        let (mut x, mut y, mut row_height) = (pad, pad, 0);
+        let mut glyphs = BTreeMap::new();
+        for (c, (_, glyph)) in crops.iter_mut() {
+            if x + glyph.width + pad > width {
+                x = pad;
+                y += row_height + pad;
+                row_height = 0;
+            }
+            glyph.x = x;
+            glyph.y = y;
+            x += glyph.width + pad;
+            row_height = row_height.max(glyph.height);
+            glyphs.insert(*c, *glyph);
+        }
+        let height = y + row_height + pad;
+
+        let mut data = vec![0; width * height];
+        for (_, (pixels, glyph)) in crops.iter() {
+            for row in 0..glyph.height {
+                let at = (glyph.y + row) * width + glyph.x;
+                data[at..at + glyph.width]
+                    .copy_from_slice(&pixels[row * glyph.width..(row + 1) * glyph.width]);
+            }
+        }
+
+        Atlas {
+            cell_width: self.cell_width,
+            cell_height: self.cell_height,
+            bitmap: Bitmap {
+                width,
+                height,
+                data,
+            },
+            glyphs,
+        }
+    }
+}
+
+/// The inked part of a cell, and its size and bearing.
+fn crop(bitmap: &Bitmap) -> (Vec<u8>, AtlasGlyph) {
+    let inked = |x: usize, y: usize| bitmap.get(x, y) != 0;
+    let rows: Vec<usize> = (0..bitmap.height)
+        .filter(|y| (0..bitmap.width).any(|x| inked(x, *y)))
+        .collect();
+    let columns: Vec<usize> = (0..bitmap.width)
+        .filter(|x| (0..bitmap.height).any(|y| inked(*x, y)))
+        .collect();
+    let (top, left) = match (rows.first(), columns.first()) {
+        (Some(top), Some(left)) => (*top, *left),
+        _ => return (Vec::new(), AtlasGlyph::default()),
+    };
+    let (width, height) = (
+        columns[columns.len() - 1] + 1 - left,
+        rows[rows.len() - 1] + 1 - top,
+    );
+
+    let mut pixels = Vec::with_capacity(width * height);
+    for y in top..top + height {
+        let at = y * bitmap.width + left;
+        pixels.extend_from_slice(&bitmap.data[at..at + width]);
+    }
+    let glyph = AtlasGlyph {
+        width,
+        height,
+        bearing: (left, top),
+        ..AtlasGlyph::default()
+    };
+    (pixels, glyph)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+
+    use super::*;
+    use crate::drawing_command::{DotShape, Metrics};
+
+    #[test]
+    fn glyphs_match_their_cells() {
+        let font = Font::new(Metrics::<f64>::default());
+        let atlas = AtlasBuilder::new(&font, 9, 18).build();
+        assert_eq!(atlas.glyphs.len(), font.code_points().count());
+
+        for (c, glyph) in atlas.glyphs.iter() {
+            let cell = font.render_cell(*c, 9, 18, 2);
+            for y in 0..18 {
+                for x in 0..9 {
+                    let (left, top) = glyph.bearing;
+                    let inside = (left..left + glyph.width).contains(&x)
+                        && (top..top + glyph.height).contains(&y);
+                    let expected = if inside {
+                        atlas.bitmap.get(glyph.x + x - left, glyph.y + y - top)
+                    } else {
+                        0
+                    };
+                    assert_eq!(cell.get(x, y), expected, "{:X} at {},{}", c, x, y);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn is_deterministic() {
+        let font = Font::new(Metrics::<f32>::default());
+        let a = AtlasBuilder::new(&font, 8, 17).build();
+        let b = AtlasBuilder::new(&font, 8, 17).build();
+        assert_eq!(a, b);
+
+        // so is the hash of the font, which tells fonts with other dots apart
+        let hash = |font: &Font<f32>| {
+            let mut hasher = DefaultHasher::new();
+            font.hash(&mut hasher);
+            hasher.finish()
+        };
+        let same = Font::new(Metrics::<f32>::default());
+        let square = Font::new(Metrics::<f32>::default()).dot_shape(DotShape::Square);
+        assert_eq!(hash(&font), hash(&same));
+        assert_ne!(hash(&font), hash(&square));
+    }
+}
@@ -0,0 +1,138 @@
+use std::fmt::Write;
+use std::ops::{AddAssign, SubAssign};
+
+use num::Float;
+
+use crate::font::{CodePointError, Font};
+
+/// Writes the glyphs of a [`Font`] as a BDF 2.1 bitmap font, for X11.
+///
+/// The glyphs of the chosen code points, by default
+/// [`Font::default_code_points`], are rendered with [`Font::render_cell`]
+/// into cells of one pixel size, so lines connect exactly between
+/// neighbouring cells, and encoded by code point in the ISO 10646 charset.
+/// The resolution is 72 dpi, making points and pixels the same.
+pub struct BdfWriter<'a, F: Float> {
+    font: &'a Font<F>,
+    family_name: &'a str,
+    cell_width: usize,
+    cell_height: usize,
+    thickness: usize,
+    code_points: Vec<u32>,
+}
+
+impl<'a, F> BdfWriter<'a, F>
+where
+    F: Float + Default + AddAssign + SubAssign + 'static,
+{
+    pub fn new(
+        font: &'a Font<F>,
+        family_name: &'a str,
+        cell_width: usize,
+        cell_height: usize,
+    ) -> BdfWriter<'a, F> {
+        BdfWriter {
+            font,
+            family_name,
+            cell_width,
+            cell_height,
+            thickness: font.cell_thickness(cell_width),
+            code_points: font.default_code_points().collect(),
+        }
+    }
+
+    /// The code points to include, all with a glyph in the font.
+    pub fn code_points(
+        mut self,
+        code_points: impl IntoIterator<Item = u32>,
+    ) -> Result<Self, CodePointError> {
+        self.code_points = self.font.select(code_points)?;
+        Ok(self)
+    }
+
+    /// Width of light lines, in pixels.
+    pub fn thickness(mut self, thickness: usize) -> Self {
+        self.thickness = thickness;
+        self
+    }
+
+    pub fn write(&self) -> String {
+        let (width, height) = (self.cell_width, self.cell_height);
+        let descent = self.font.cell_descent(height);
+        let ascent = height - descent;
+        let family = self.family_name.replace('-', " ");
+
+        let mut bdf = String::new();
+        writeln!(bdf, "STARTFONT 2.1").unwrap();
+        writeln!(
+            bdf,
+            "FONT -misc-{}-Medium-R-Normal--{}-{}-72-72-C-{}-ISO10646-1",
+            family,
+            height,
+            height * 10,
+            width * 10
+        )
+        .unwrap();
+        writeln!(bdf, "SIZE {} 72 72", height).unwrap();
+        writeln!(bdf, "FONTBOUNDINGBOX {} {} 0 -{}", width, height, descent).unwrap();
+        writeln!(bdf, "STARTPROPERTIES 10").unwrap();
+        writeln!(bdf, "FAMILY_NAME \"{}\"", family).unwrap();
+        writeln!(bdf, "WEIGHT_NAME \"Medium\"").unwrap();
+        writeln!(bdf, "PIXEL_SIZE {}", height).unwrap();
+        writeln!(bdf, "POINT_SIZE {}", height * 10).unwrap();
+        writeln!(bdf, "SPACING \"C\"").unwrap();
+        writeln!(bdf, "AVERAGE_WIDTH {}", width * 10).unwrap();
+        writeln!(bdf, "CHARSET_REGISTRY \"ISO10646\"").unwrap();
+        writeln!(bdf, "CHARSET_ENCODING \"1\"").unwrap();
+        writeln!(bdf, "FONT_ASCENT {}", ascent).unwrap();
+        writeln!(bdf, "FONT_DESCENT {}", descent).unwrap();
+        writeln!(bdf, "ENDPROPERTIES").unwrap();
+        writeln!(bdf, "CHARS {}", self.code_points.len()).unwrap();
+
+        let row_bytes = width.div_ceil(8);
+        for c in self.code_points.iter().copied() {
+            let bitmap = self.font.render_cell(c, width, height, self.thickness);
+            writeln!(bdf, "STARTCHAR {}", Font::<F>::glyph_name(c)).unwrap();
+            writeln!(bdf, "ENCODING {}", c).unwrap();
+            writeln!(bdf, "SWIDTH {} 0", width * 1000 / height).unwrap();
+            writeln!(bdf, "DWIDTH {} 0", width).unwrap();
+            writeln!(bdf, "BBX {} {} 0 -{}", width, height, descent).unwrap();
+            writeln!(bdf, "BITMAP").unwrap();
+            for row in bitmap.to_monochrome().chunks(row_bytes) {
+                for byte in row {
+                    write!(bdf, "{:02X}", byte).unwrap();
+                }
+                bdf.push('\n');
+            }
+            writeln!(bdf, "ENDCHAR").unwrap();
+        }
+        writeln!(bdf, "ENDFONT").unwrap();
+        bdf
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::drawing_command::Metrics;
+
+    #[test]
+    fn lines_connect() {
+        let font = Font::new(Metrics::<f64>::default());
+        let bdf = BdfWriter::new(&font, "Box Drawing", 8, 16).write();
+        assert!(bdf.starts_with("STARTFONT 2.1\n"));
+        assert!(bdf.contains("\nCHARS 160\n"));
+
+        // a light horizontal line, 2 pixels wide and filling the cell
+        let glyph = bdf.split("ENCODING 9472\n").nth(1).unwrap();
+        let rows: Vec<&str> = glyph.lines().skip(4).take(16).collect();
+        assert_eq!(rows.iter().filter(|row| **row == "FF").count(), 2);
+        assert_eq!(rows.iter().filter(|row| **row == "00").count(), 14);
+
+        let bdf = BdfWriter::new(&font, "Box Drawing", 8, 16)
+            .code_points([0x2800])
+            .unwrap()
+            .write();
+        assert!(bdf.contains("\nCHARS 1\nSTARTCHAR brailleblank\nENCODING 10240\n"));
+    }
+}
@@ -0,0 +1,73 @@
+//! Renders the box drawing and block element glyphs, U+2500 to U+259F, into
+//! BDF and PSF2 bitmap fonts, one pair per cell size.
+
+use std::{fs, path::Path, process};
+
+use box_drawing::prelude::*;
+
+const USAGE: &str = "usage: bitmap-fonts [--thickness <pixels>] <out-dir> <WxH>...
+
+Writes the box drawing and block element glyphs, U+2500 to U+259F, as
+box-drawing-<W>x<H>.bdf for X11 and box-drawing-<W>x<H>.psf for the Linux
+console, for every cell size given, e.g. 8x16.
+
+  --thickness <pixels>   width of light lines, instead of 4/15 of the
+                         cell width";
+
+fn main() {
+    let mut thickness = None;
+    let mut dir = None;
+    let mut sizes = Vec::new();
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--thickness" => match args.next().and_then(|v| v.parse::<usize>().ok()) {
+                Some(v) if v > 0 => thickness = Some(v),
+                _ => fail("--thickness needs a positive number"),
+            },
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            _ if arg.starts_with('-') => fail(&format!("unknown option `{}`", arg)),
+            _ if dir.is_none() => dir = Some(arg),
+            _ => match arg
+                .split_once('x')
+                .and_then(|(w, h)| Some((w.parse().ok()?, h.parse().ok()?)))
+            {
+                Some((w, h)) if w > 0 && h > 0 => sizes.push((w, h)),
+                _ => fail(&format!("`{}` is not a cell size", arg)),
+            },
+        }
+    }
+    let dir = dir.unwrap_or_else(|| fail("no output directory given"));
+    if sizes.is_empty() {
+        fail("no cell size given");
+    }
+
+    let font = Font::new(Metrics::<f64>::default());
+    for (width, height) in sizes {
+        let thickness = thickness.unwrap_or_else(|| font.cell_thickness(width));
+        let name = Path::new(&dir).join(format!("box-drawing-{}x{}", width, height));
+
+        let bdf = BdfWriter::new(&font, "Box Drawing", width, height)
+            .thickness(thickness)
+            .write();
+        let psf = PsfWriter::new(&font, width, height)
+            .thickness(thickness)
+            .write();
+        for (path, data) in [
+            (name.with_extension("bdf"), bdf.into_bytes()),
+            (name.with_extension("psf"), psf),
+        ] {
+            fs::write(&path, data).unwrap_or_else(|e| fail(&format!("{}: {}", path.display(), e)));
+            println!("{}", path.display());
+        }
+    }
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("bitmap-fonts: {}\n\n{}", message, USAGE);
+    process::exit(2);
+}
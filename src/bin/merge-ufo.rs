@@ -0,0 +1,77 @@
+//! Adds the box drawing glyphs to an existing UFO source, drawn to match its
+//! advance width and line height.
+
+use std::process;
+
+use box_drawing::prelude::*;
+
+const USAGE: &str = "usage: merge-ufo [--replace] [--stroke <units>] [--all] <font.ufo>
+
+Draws the box drawing and block element glyphs, U+2500 to U+259F, into the
+default layer of a UFO 3 source, sized from its advance widths and vertical
+metrics.
+
+  --replace          redraw glyphs the source already has, instead of
+                     keeping them
+  --stroke <units>   stroke weight of light lines, instead of 4/15 of the
+                     advance width
+  --all              also draw the other glyphs there are recipes for: the
+                     bracket pieces, geometric shapes, Braille patterns and
+                     legacy computing symbols";
+
+fn main() {
+    let mut policy = MergePolicy::Keep;
+    let mut stroke = None;
+    let mut all = false;
+    let mut path = None;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--replace" => policy = MergePolicy::Replace,
+            "--stroke" => match args.next().and_then(|v| v.parse::<f64>().ok()) {
+                Some(v) if v > 0. => stroke = Some(v),
+                _ => fail("--stroke needs a positive number"),
+            },
+            "--all" => all = true,
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            _ if arg.starts_with('-') => fail(&format!("unknown option `{}`", arg)),
+            _ if path.is_none() => path = Some(arg),
+            _ => fail("only one UFO at a time"),
+        }
+    }
+    let path = path.unwrap_or_else(|| fail("no UFO given"));
+
+    let mut ufo = Ufo::open(&path).unwrap_or_else(|e| fail(&format!("{}: {}", path, e)));
+    let mut metrics = ufo
+        .metrics::<f64>()
+        .unwrap_or_else(|e| fail(&format!("{}: {}", path, e)));
+    if let Some(stroke) = stroke {
+        metrics = metrics.with_stroke(stroke);
+    }
+
+    let font = Font::new(metrics);
+    let code_points: Vec<u32> = if all {
+        font.code_points().collect()
+    } else {
+        font.default_code_points().collect()
+    };
+    let report = ufo
+        .merge(&font, code_points, policy)
+        .unwrap_or_else(|e| fail(&format!("{}: {}", path, e)));
+    println!(
+        "{}: {} added, {} replaced, {} kept",
+        path,
+        report.added.len(),
+        report.replaced.len(),
+        report.kept.len()
+    );
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("merge-ufo: {}\n\n{}", message, USAGE);
+    process::exit(2);
+}
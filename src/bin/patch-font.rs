@@ -0,0 +1,92 @@
+//! Adds the box drawing glyphs to a compiled TrueType font, drawn to match
+//! its advance width and line height.
+
+use std::{fs, process};
+
+use box_drawing::prelude::*;
+
+const USAGE: &str = "usage: patch-font [options] <in.ttf> <out.ttf>
+
+Draws the box drawing and block element glyphs, U+2500 to U+259F, into a
+TrueType font, sized from its advance widths and vertical metrics. Fonts
+with CFF outlines are not supported.
+
+  --replace          redraw glyphs the font already has, instead of
+                     keeping them
+  --stroke <units>   stroke weight of light lines, instead of the underline
+                     thickness
+  --all              also draw the other glyphs there are recipes for: the
+                     bracket pieces, geometric shapes, Braille patterns and
+                     legacy computing symbols
+  --powerline        also draw the Powerline separators, U+E0B0 to U+E0B7
+  --progress-bar     also draw the progress bar and spinner, U+EE00 to U+EE0B
+  --git-branch       also draw the branch drawing symbols, U+F5D0 to U+F60D";
+
+fn main() {
+    let mut policy = MergePolicy::Keep;
+    let mut stroke = None;
+    let mut all = false;
+    let mut private_use = Vec::new();
+    let mut paths = Vec::new();
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--replace" => policy = MergePolicy::Replace,
+            "--stroke" => match args.next().and_then(|v| v.parse::<f64>().ok()) {
+                Some(v) if v > 0. => stroke = Some(v),
+                _ => fail("--stroke needs a positive number"),
+            },
+            "--all" => all = true,
+            "--powerline" => private_use.push(PrivateUse::Powerline),
+            "--progress-bar" => private_use.push(PrivateUse::ProgressBar),
+            "--git-branch" => private_use.push(PrivateUse::GitBranch),
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            _ if arg.starts_with('-') => fail(&format!("unknown option `{}`", arg)),
+            _ if paths.len() < 2 => paths.push(arg),
+            _ => fail("too many files"),
+        }
+    }
+    if paths.len() < 2 {
+        fail("expected an input and an output font");
+    }
+    let (input, output) = (&paths[0], &paths[1]);
+
+    let data = fs::read(input).unwrap_or_else(|e| fail(&format!("{}: {}", input, e)));
+    let file = FontFile::parse(&data).unwrap_or_else(|e| fail(&format!("{}: {}", input, e)));
+    let mut metrics = file
+        .metrics::<f64>()
+        .unwrap_or_else(|e| fail(&format!("{}: {}", input, e)));
+    if let Some(stroke) = stroke {
+        metrics = metrics.with_stroke(stroke);
+    }
+
+    let font = private_use
+        .into_iter()
+        .fold(Font::new(metrics), Font::private_use);
+    let mut patcher = FontPatcher::new(&font).policy(policy);
+    if all {
+        patcher = patcher
+            .code_points(font.code_points())
+            .unwrap_or_else(|e| fail(&e.to_string()));
+    }
+    let (patched, report) = patcher
+        .patch(&data)
+        .unwrap_or_else(|e| fail(&format!("{}: {}", input, e)));
+    fs::write(output, patched).unwrap_or_else(|e| fail(&format!("{}: {}", output, e)));
+    println!(
+        "{}: {} added, {} replaced, {} kept",
+        output,
+        report.added.len(),
+        report.replaced.len(),
+        report.kept.len()
+    );
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("patch-font: {}\n\n{}", message, USAGE);
+    process::exit(2);
+}
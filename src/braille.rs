@@ -0,0 +1,102 @@
+//! Recipes for the Braille patterns, U+2800 to U+28FF.
+
+use std::ops::{AddAssign, SubAssign};
+
+use num::Float;
+
+use crate::commands::{Box_Builder, DotBuilder};
+use crate::drawing_command::{DotShape, Metrics, Point};
+use crate::font::{Configure, Recipe};
+use crate::legacy::cell_numbers;
+
+/// Rows of dots, top to bottom, with the bits of their left and right dots.
+const ROWS: [(u32, u32); 4] = [(0, 3), (1, 4), (2, 5), (6, 7)];
+
+/// Centre of the dot in `column` of 2 and `row` of 4 from the top.
+///
+/// Dots sit in the middle of the cells of a 2×4 grid over the width and the
+/// block area, so their spacing is the same within a character and across
+/// neighbouring ones.
+fn center<F: Float>(m: &Metrics<F>, column: u32, row: u32) -> Point<F> {
+    let f = |v: u32| F::from(v).unwrap();
+    Point::new(
+        m.width * f(2 * column + 1) / f(4),
+        m.block_top.y - m.block_height * f(2 * row + 1) / f(8),
+    )
+}
+
+/// Dot radius, a quarter of the smaller spacing between dots.
+fn radius<F: Float>(m: &Metrics<F>) -> F {
+    let f = |v: u32| F::from(v).unwrap();
+    (m.width / f(2)).min(m.block_height / f(4)) / f(4)
+}
+
+/// The Braille pattern `c`, with dots of the given shape.
+pub(crate) fn pattern<F>(c: u32, shape: DotShape) -> Recipe<F>
+where
+    F: Float + AddAssign + SubAssign + 'static,
+{
+    let pattern = c - 0x2800;
+    let mut commands: Vec<Configure<F>> = Vec::new();
+    for (row, bits) in ROWS.iter().enumerate() {
+        for (column, bit) in [bits.0, bits.1].into_iter().enumerate() {
+            if pattern >> bit & 1 == 0 {
+                continue;
+            }
+            let (column, row) = (column as u32, row as u32);
+            commands.push(Box::new(move |m: &Metrics<F>| {
+                let (center, radius) = (center(m, column, row), radius(m));
+                match shape {
+                    DotShape::Round => {
+                        let mut builder = DotBuilder::default();
+                        builder.center(center).radius(radius);
+                        builder.build().unwrap().into()
+                    }
+                    DotShape::Square => {
+                        let mut builder = Box_Builder::default();
+                        builder.start(Point::new(center.x - radius, center.y - radius));
+                        builder.end(Point::new(center.x + radius, center.y + radius));
+                        builder.build().unwrap().into()
+                    }
+                }
+            }));
+        }
+    }
+    let name = match pattern {
+        0 => "brailleblank".to_string(),
+        _ => format!("dots{}", cell_numbers(pattern)),
+    };
+    Recipe::new(c, name, commands)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::drawing_command::{DotShape, Metrics};
+    use crate::font::Font;
+
+    #[test]
+    fn dots_form_an_even_grid() {
+        let font = Font::new(Metrics::<f64>::default()).dot_shape(DotShape::Square);
+        assert_eq!(Font::<f64>::glyph_name(0x2800), "brailleblank");
+        assert_eq!(Font::<f64>::glyph_name(0x281B), "dots1245");
+
+        // the eight dots of a full cell are squares of the same size, a
+        // spacing apart, also across the cell edges
+        let cell = font.render_cell(0x28FF, 12, 24, 2);
+        let starts = |inked: &dyn Fn(usize) -> bool, len: usize| -> Vec<usize> {
+            (0..len)
+                .filter(|i| inked(*i) && (*i == 0 || !inked(i - 1)))
+                .collect()
+        };
+        let columns = starts(&|x| cell.get(x, 3) != 0, 12);
+        let rows = starts(&|y| cell.get(3, y) != 0, 24);
+        assert_eq!((columns.len(), rows.len()), (2, 4));
+        assert_eq!(
+            (columns[1] - columns[0], columns[0] + 12 - columns[1]),
+            (6, 6)
+        );
+        assert!(rows.windows(2).all(|pair| pair[1] - pair[0] == 6));
+        assert_eq!(rows[0] + 24 - rows[3], 6);
+        assert_eq!(cell.data.iter().filter(|v| **v == 255).count(), 8 * 3 * 3);
+    }
+}
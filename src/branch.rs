@@ -0,0 +1,305 @@
+//! Recipes for the branch drawing symbols, U+F5D0 to U+F60D, as drawn by
+//! kitty for git graphs: lines fading out, forking and merging arcs, and
+//! commit nodes.
+//!
+//! Lines and arcs are those of the light box drawing characters, so the
+//! graphs connect with them.
+
+use std::ops::{AddAssign, SubAssign};
+
+use num::Float;
+
+use crate::commands::{
+    ArcBuilder, DotBuilder, HorBarBuilder, HorLineBuilder, VertBarBuilder, VertLineBuilder,
+};
+use crate::drawing_command::{Metrics, Point, Side};
+use crate::font::{Configure, Recipe};
+
+const RIGHT: u32 = 0b0001;
+const LEFT: u32 = 0b0010;
+const DOWN: u32 = 0b0100;
+const UP: u32 = 0b1000;
+
+/// U+F5DA to U+F5ED, the vertical or horizontal line, if any, and the arcs
+/// joined to it, by their side as in [`arc`].
+#[rustfmt::skip]
+const FORKS: [(Option<bool>, &[Side]); 20] = [
+    (Some(true), &[Side::BottomLeft]), // F5DA
+    (Some(true), &[Side::TopLeft]), // F5DB
+    (None, &[Side::BottomLeft, Side::TopLeft]), // F5DC
+    (Some(true), &[Side::BottomRight]), // F5DD
+    (Some(true), &[Side::TopRight]), // F5DE
+    (None, &[Side::TopRight, Side::BottomRight]), // F5DF
+    (Some(false), &[Side::TopRight]), // F5E0
+    (Some(false), &[Side::TopLeft]), // F5E1
+    (None, &[Side::TopLeft, Side::TopRight]), // F5E2
+    (Some(false), &[Side::BottomRight]), // F5E3
+    (Some(false), &[Side::BottomLeft]), // F5E4
+    (None, &[Side::BottomLeft, Side::BottomRight]), // F5E5
+    (Some(true), &[Side::BottomLeft, Side::TopLeft]), // F5E6
+    (Some(true), &[Side::TopRight, Side::BottomRight]), // F5E7
+    (Some(false), &[Side::TopRight, Side::TopLeft]), // F5E8
+    (Some(false), &[Side::BottomLeft, Side::BottomRight]), // F5E9
+    (Some(true), &[Side::TopLeft, Side::BottomRight]), // F5EA
+    (Some(true), &[Side::TopRight, Side::BottomLeft]), // F5EB
+    (Some(false), &[Side::TopLeft, Side::BottomRight]), // F5EC
+    (Some(false), &[Side::TopRight, Side::BottomLeft]), // F5ED
+];
+
+/// U+F5EE to U+F60D, the lines leaving each commit node, for a filled and
+/// an open node in turn.
+const COMMITS: [u32; 16] = [
+    0,
+    RIGHT,
+    LEFT,
+    RIGHT | LEFT,
+    DOWN,
+    UP,
+    DOWN | UP,
+    RIGHT | DOWN,
+    LEFT | DOWN,
+    RIGHT | UP,
+    LEFT | UP,
+    UP | DOWN | RIGHT,
+    UP | DOWN | LEFT,
+    LEFT | RIGHT | DOWN,
+    LEFT | RIGHT | UP,
+    LEFT | RIGHT | DOWN | UP,
+];
+
+/// Dashes in a line fading out, across and along.
+const FADING_DASHES: (u32, u32) = (4, 5);
+
+fn hor_bar<F>() -> Configure<F>
+where
+    F: Float + AddAssign + SubAssign + 'static,
+{
+    Box::new(|_: &Metrics<F>| HorBarBuilder::default().build().unwrap().into())
+}
+
+fn vert_bar<F>() -> Configure<F>
+where
+    F: Float + AddAssign + SubAssign + 'static,
+{
+    Box::new(|_: &Metrics<F>| VertBarBuilder::default().build().unwrap().into())
+}
+
+/// The rounded corner of U+256D to U+2570 for `side`: top left for U+256D,
+/// top right for U+256E, bottom right for U+256F and bottom left for U+2570.
+fn arc<F>(side: Side) -> Configure<F>
+where
+    F: Float + AddAssign + SubAssign + 'static,
+{
+    Box::new(move |m: &Metrics<F>| {
+        let two = F::one() + F::one();
+        let (y, x) = match side {
+            Side::TopLeft => (m.median - m.height / two, m.width),
+            Side::TopRight => (m.median - m.height / two, F::zero()),
+            Side::BottomLeft => (m.median + m.height / two, m.width),
+            Side::BottomRight => (m.median + m.height / two, F::zero()),
+        };
+        let mut builder = ArcBuilder::default();
+        builder
+            .start(Point::new(m.width / two, y))
+            .end(Point::new(x, m.median))
+            .side(side)
+            .stroke(m.stroke)
+            .radius(m.radius)
+            .butt(m.butt);
+        builder.build().unwrap().into()
+    })
+}
+
+/// A line fading out towards `direction`, in dashes shorter and shorter.
+fn fading<F>(direction: u32) -> Vec<Configure<F>>
+where
+    F: Float + AddAssign + SubAssign + 'static,
+{
+    let dashes = match direction {
+        RIGHT | LEFT => FADING_DASHES.0,
+        _ => FADING_DASHES.1,
+    };
+    (0..dashes)
+        .map(|i| -> Configure<F> {
+            Box::new(move |m: &Metrics<F>| {
+                let f = |v: u32| F::from(v).unwrap();
+                let two = f(2);
+                // the first dash joins the next cell, each one after it is
+                // shorter by the same amount
+                let (length, butt) = match direction {
+                    RIGHT | LEFT => (m.width, m.butt),
+                    _ => (m.height, F::zero()),
+                };
+                let step = length / f(dashes);
+                let start = step * f(i);
+                let end = start + step * f(dashes - i) / f(dashes + 1);
+                let butt = if i == 0 { butt } else { F::zero() };
+                match direction {
+                    RIGHT | LEFT => {
+                        let (start, end) = match direction {
+                            RIGHT => (start, end),
+                            _ => (m.width - end, m.width - start),
+                        };
+                        let (butt_left, butt_right) = match direction {
+                            RIGHT => (butt, F::zero()),
+                            _ => (F::zero(), butt),
+                        };
+                        let mut builder = HorLineBuilder::default();
+                        builder
+                            .start(Point::new(start, m.median))
+                            .end(Point::new(end, m.median))
+                            .stroke(m.stroke)
+                            .butt_left(butt_left)
+                            .butt_right(butt_right);
+                        builder.build().unwrap().into()
+                    }
+                    _ => {
+                        let bottom = m.median - m.height / two;
+                        let (start, end) = match direction {
+                            UP => (bottom + start, bottom + end),
+                            _ => (bottom + m.height - end, bottom + m.height - start),
+                        };
+                        let mut builder = VertLineBuilder::default();
+                        builder
+                            .start(Point::new(m.width / two, start))
+                            .end(Point::new(m.width / two, end))
+                            .stroke(m.stroke);
+                        builder.build().unwrap().into()
+                    }
+                }
+            })
+        })
+        .collect()
+}
+
+/// A commit node, filled or open, with light lines from its edge to the
+/// sides of the cell in `lines`.
+fn commit<F>(lines: u32, open: bool) -> Vec<Configure<F>>
+where
+    F: Float + AddAssign + SubAssign + 'static,
+{
+    let radius = |m: &Metrics<F>| m.width / F::from(3).unwrap();
+    let mut commands: Vec<Configure<F>> = vec![Box::new(move |m: &Metrics<F>| {
+        let mut builder = DotBuilder::default();
+        builder
+            .center(Point::new(m.width / F::from(2).unwrap(), m.median))
+            .radius(radius(m));
+        if open {
+            builder.stroke(m.stroke);
+        }
+        builder.build().unwrap().into()
+    })];
+    for line in [RIGHT, LEFT, DOWN, UP] {
+        if lines & line == 0 {
+            continue;
+        }
+        commands.push(Box::new(move |m: &Metrics<F>| {
+            let two = F::one() + F::one();
+            let (x, y) = (m.width / two, m.median);
+            let (bottom, top) = (y - m.height / two, y + m.height / two);
+            let r = radius(m);
+            match line {
+                RIGHT | LEFT => {
+                    let mut builder = HorLineBuilder::default();
+                    if line == RIGHT {
+                        builder
+                            .start(Point::new(x + r, y))
+                            .end(Point::new(m.width, y))
+                            .butt_left(F::zero())
+                            .butt_right(m.butt);
+                    } else {
+                        builder
+                            .start(Point::new(F::zero(), y))
+                            .end(Point::new(x - r, y))
+                            .butt_left(m.butt)
+                            .butt_right(F::zero());
+                    }
+                    builder.stroke(m.stroke);
+                    builder.build().unwrap().into()
+                }
+                _ => {
+                    let mut builder = VertLineBuilder::default();
+                    if line == UP {
+                        builder.start(Point::new(x, y + r)).end(Point::new(x, top));
+                    } else {
+                        builder
+                            .start(Point::new(x, bottom))
+                            .end(Point::new(x, y - r));
+                    }
+                    builder.stroke(m.stroke);
+                    builder.build().unwrap().into()
+                }
+            }
+        }));
+    }
+    commands
+}
+
+/// The branch drawing symbol `c`.
+pub(crate) fn symbol<F>(c: u32) -> Recipe<F>
+where
+    F: Float + AddAssign + SubAssign + 'static,
+{
+    let commands = match c {
+        0xF5D0 => vec![hor_bar()],
+        0xF5D1 => vec![vert_bar()],
+        0xF5D2 => fading(RIGHT),
+        0xF5D3 => fading(LEFT),
+        0xF5D4 => fading(DOWN),
+        0xF5D5 => fading(UP),
+        0xF5D6 => vec![arc(Side::TopLeft)],
+        0xF5D7 => vec![arc(Side::TopRight)],
+        0xF5D8 => vec![arc(Side::BottomLeft)],
+        0xF5D9 => vec![arc(Side::BottomRight)],
+        0xF5DA..=0xF5ED => {
+            let (line, arcs) = FORKS[(c - 0xF5DA) as usize];
+            let line = line.map(|vertical| if vertical { vert_bar() } else { hor_bar() });
+            line.into_iter()
+                .chain(arcs.iter().map(|side| arc(*side)))
+                .collect()
+        }
+        _ => {
+            let i = (c - 0xF5EE) as usize;
+            commit(COMMITS[i / 2], i % 2 == 1)
+        }
+    };
+    Recipe::new(c, format!("uni{:04X}", c), commands)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::drawing_command::Metrics;
+    use crate::font::{Font, PrivateUse};
+
+    #[test]
+    fn branches_join_box_drawing_lines() {
+        let font = Font::new(Metrics::<f64>::default());
+        assert!(!font.has_glyph(0xF5D0));
+        let font = font.private_use(PrivateUse::GitBranch);
+        assert_eq!(
+            font.code_points()
+                .filter(|c| (0xF5D0..=0xF60D).contains(c))
+                .count(),
+            62
+        );
+
+        // lines and arcs reach the cell edges where those of U+253C do, and
+        // open commit nodes are empty inside
+        let cross = font.render_cell(0x253C, 10, 20, 1);
+        for c in [0xF5D0, 0xF5D1, 0xF5E6, 0xF60C, 0xF60D] {
+            let cell = font.render_cell(c, 10, 20, 1);
+            for (x, y) in [(0, 10), (9, 10), (5, 0), (5, 19)] {
+                let expected = match c {
+                    0xF5D0 => y == 10,
+                    0xF5D1 => x == 5,
+                    0xF5E6 => x != 0,
+                    _ => true,
+                };
+                let expected = if expected { cross.get(x, y) } else { 0 };
+                assert_eq!(cell.get(x, y), expected, "{:X} at {},{}", c, x, y);
+            }
+        }
+        let open = font.render_cell(0xF60D, 10, 20, 1);
+        assert_eq!(open.get(5, 10), 0);
+    }
+}
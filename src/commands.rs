@@ -2,7 +2,7 @@ use std::ops::{AddAssign, SubAssign};
 
 use box_drawing_derive::{Parameters, PositionalArgs};
 use derive_builder::Builder;
-use num::{CheckedAdd, Float};
+use num::Float;
 
 use crate::drawing_command::{Canvas, Direction, DrawingCommand, Point, Shade, Side};
 
@@ -28,6 +28,10 @@ where
     VertLine(VertLine<F>),
     HorSplitHalfBar(HorSplitHalfBar<F>),
     VertSplitHalfBar(VertSplitHalfBar<F>),
+    Polygon(Polygon<F>),
+    Dot(Dot<F>),
+    HalfEllipse(HalfEllipse<F>),
+    RingArc(RingArc<F>),
 }
 
 macro_rules! impl_into {
@@ -72,8 +76,12 @@ impl_into!(HorLine -> Commands);
 impl_into!(VertLine -> Commands);
 impl_into!(HorSplitHalfBar -> Commands);
 impl_into!(VertSplitHalfBar -> Commands);
+impl_into!(Polygon -> Commands);
+impl_into!(Dot -> Commands);
+impl_into!(HalfEllipse -> Commands);
+impl_into!(RingArc -> Commands);
 
-impl<F: num::Float + AddAssign + CheckedAdd + SubAssign> Commands<F> {
+impl<F: num::Float + AddAssign + SubAssign> Commands<F> {
     pub fn execute<C: Canvas<F>>(&self, ctx: &DrawingCommand<C, F>) {
         match self {
             Self::HorBar(HorBar {
@@ -181,19 +189,41 @@ impl<F: num::Float + AddAssign + CheckedAdd + SubAssign> Commands<F> {
                 butt_bot,
                 butt_top,
             }) => ctx.vert_split_half_bar(*side, *fatness, *butt_bot, *butt_top),
+            Commands::Polygon(Polygon { points }) => ctx.polygon(points),
+            Commands::Dot(Dot {
+                center,
+                radius,
+                stroke,
+            }) => match stroke {
+                Some(stroke) => ctx.ring(center, *radius, *stroke),
+                None => ctx.dot(center, *radius),
+            },
+            Commands::HalfEllipse(HalfEllipse {
+                center,
+                radius_x,
+                radius_y,
+                stroke,
+            }) => ctx.half_ellipse(center, *radius_x, *radius_y, *stroke),
+            Commands::RingArc(RingArc {
+                center,
+                radius,
+                stroke,
+                start,
+                end,
+            }) => ctx.ring_arc(center, *radius, *stroke, *start, *end),
         }
     }
 }
 
 #[derive(Clone, Debug, Default, Builder, PositionalArgs)]
 pub struct HorBar<F> {
-    #[builder(setter(into, strip_option))]
+    #[builder(setter(into, strip_option), default)]
     fatness: Option<F>,
-    #[builder(setter(into, strip_option))]
+    #[builder(setter(into, strip_option), default)]
     median: Option<F>,
-    #[builder(setter(into, strip_option))]
+    #[builder(setter(into, strip_option), default)]
     butt_left: Option<F>,
-    #[builder(setter(into, strip_option))]
+    #[builder(setter(into, strip_option), default)]
     butt_right: Option<F>,
 }
 
@@ -206,11 +236,11 @@ impl<F> HorBar<F> {
 
 #[derive(Clone, Debug, Default, Builder, PositionalArgs)]
 pub struct VertBar<F> {
-    #[builder(setter(into, strip_option))]
+    #[builder(setter(into, strip_option), default)]
     fatness: Option<F>,
-    #[builder(setter(into, strip_option))]
+    #[builder(setter(into, strip_option), default)]
     butt_bot: Option<F>,
-    #[builder(setter(into, strip_option))]
+    #[builder(setter(into, strip_option), default)]
     butt_top: Option<F>,
 }
 
@@ -224,9 +254,9 @@ impl<F> VertBar<F> {
 #[derive(Clone, Copy, Debug, Builder, PositionalArgs)]
 pub struct DashedHorLine<F> {
     step: F,
-    #[builder(setter(into, strip_option))]
+    #[builder(setter(into, strip_option), default)]
     width: Option<F>,
-    #[builder(setter(into, strip_option))]
+    #[builder(setter(into, strip_option), default)]
     stroke: Option<F>,
 }
 
@@ -248,33 +278,33 @@ impl<F> DashedHorLine<F> {
 #[derive(Parameters, Clone, Copy, Debug, Builder, PositionalArgs)]
 pub struct DashedVertLine<F> {
     step: F,
-    #[builder(setter(into, strip_option))]
+    #[builder(setter(into, strip_option), default)]
     length: Option<F>,
-    #[builder(setter(into, strip_option))]
+    #[builder(setter(into, strip_option), default)]
     stroke: Option<F>,
 }
 
 #[derive(Clone, Copy, Debug, Parameters, Builder, PositionalArgs)]
 pub struct HorHalfBar<F> {
     side: Side,
-    #[builder(setter(into, strip_option))]
+    #[builder(setter(into, strip_option), default)]
     fatness: Option<F>,
-    #[builder(setter(into, strip_option))]
+    #[builder(setter(into, strip_option), default)]
     median: Option<F>,
-    #[builder(setter(into, strip_option))]
+    #[builder(setter(into, strip_option), default)]
     butt_left: Option<F>,
-    #[builder(setter(into, strip_option))]
+    #[builder(setter(into, strip_option), default)]
     butt_right: Option<F>,
 }
 
 #[derive(Clone, Copy, Debug, Parameters, Builder, PositionalArgs)]
 pub struct VertHalfBar<F> {
     side: Side,
-    #[builder(setter(into, strip_option))]
+    #[builder(setter(into, strip_option), default)]
     fatness: Option<F>,
-    #[builder(setter(into, strip_option))]
+    #[builder(setter(into, strip_option), default)]
     butt_bot: Option<F>,
-    #[builder(setter(into, strip_option))]
+    #[builder(setter(into, strip_option), default)]
     butt_top: Option<F>,
 }
 
@@ -283,8 +313,10 @@ pub struct Box_<F>
 where
     F: Float + Clone + Copy,
 {
-    start: Point<F>,
-    end: Point<F>,
+    #[builder(setter(into, strip_option), default)]
+    start: Option<Point<F>>,
+    #[builder(setter(into, strip_option), default)]
+    end: Option<Point<F>>,
 }
 
 #[derive(Clone, Copy, Debug, Builder, PositionalArgs)]
@@ -297,7 +329,7 @@ where
     side: Side,
     stroke: F,
     radius: F,
-    #[builder(setter(into, strip_option))]
+    #[builder(setter(into, strip_option), default)]
     butt: Option<F>,
 }
 
@@ -320,9 +352,9 @@ where
 pub struct InnerCorner<F> {
     side: Side,
     fold: Side,
-    #[builder(setter(into, strip_option))]
+    #[builder(setter(into, strip_option), default)]
     fatness: Option<F>,
-    #[builder(setter(into, strip_option))]
+    #[builder(setter(into, strip_option), default)]
     corner_median: Option<F>,
 }
 
@@ -330,29 +362,29 @@ pub struct InnerCorner<F> {
 pub struct OuterCorner<F> {
     side: Side,
     fold: Side,
-    #[builder(setter(into, strip_option))]
+    #[builder(setter(into, strip_option), default)]
     fatness: Option<F>,
-    #[builder(setter(into, strip_option))]
+    #[builder(setter(into, strip_option), default)]
     corner_median: Option<F>,
 }
 
 #[derive(Clone, Copy, Debug, Builder, PositionalArgs)]
 pub struct HorSplitBar<F> {
-    #[builder(setter(into, strip_option))]
+    #[builder(setter(into, strip_option), default)]
     fatness: Option<F>,
-    #[builder(setter(into, strip_option))]
+    #[builder(setter(into, strip_option), default)]
     butt_left: Option<F>,
-    #[builder(setter(into, strip_option))]
+    #[builder(setter(into, strip_option), default)]
     butt_right: Option<F>,
 }
 
 #[derive(Clone, Copy, Debug, Builder, PositionalArgs)]
 pub struct VertSplitBar<F> {
-    #[builder(setter(into, strip_option))]
+    #[builder(setter(into, strip_option), default)]
     fatness: Option<F>,
-    #[builder(setter(into, strip_option))]
+    #[builder(setter(into, strip_option), default)]
     butt_bot: Option<F>,
-    #[builder(setter(into, strip_option))]
+    #[builder(setter(into, strip_option), default)]
     butt_top: Option<F>,
 }
 
@@ -364,9 +396,9 @@ where
     start: Point<F>,
     end: Point<F>,
     stroke: F,
-    #[builder(setter(into, strip_option))]
+    #[builder(setter(into, strip_option), default)]
     butt_left: Option<F>,
-    #[builder(setter(into, strip_option))]
+    #[builder(setter(into, strip_option), default)]
     butt_right: Option<F>,
 }
 
@@ -378,9 +410,9 @@ where
     start: Point<F>,
     end: Point<F>,
     stroke: F,
-    #[builder(setter(into, strip_option))]
+    #[builder(setter(into, strip_option), default)]
     butt_bot: Option<F>,
-    #[builder(setter(into, strip_option))]
+    #[builder(setter(into, strip_option), default)]
     butt_top: Option<F>,
 }
 
@@ -390,11 +422,11 @@ where
     F: Float + Clone + Copy,
 {
     side: Side,
-    #[builder(setter(into, strip_option))]
+    #[builder(setter(into, strip_option), default)]
     fatness: Option<F>,
-    #[builder(setter(into, strip_option))]
+    #[builder(setter(into, strip_option), default)]
     butt_left: Option<F>,
-    #[builder(setter(into, strip_option))]
+    #[builder(setter(into, strip_option), default)]
     butt_right: Option<F>,
 }
 
@@ -404,10 +436,53 @@ where
     F: Float + Clone + Copy,
 {
     side: Side,
-    #[builder(setter(into, strip_option))]
+    #[builder(setter(into, strip_option), default)]
     fatness: Option<F>,
-    #[builder(setter(into, strip_option))]
+    #[builder(setter(into, strip_option), default)]
     butt_bot: Option<F>,
-    #[builder(setter(into, strip_option))]
+    #[builder(setter(into, strip_option), default)]
     butt_top: Option<F>,
 }
+
+#[derive(Clone, Debug, Builder, PositionalArgs)]
+pub struct Polygon<F>
+where
+    F: Float + Clone + Copy,
+{
+    points: Vec<Point<F>>,
+}
+
+#[derive(Clone, Copy, Debug, Builder, PositionalArgs)]
+pub struct Dot<F>
+where
+    F: Float + Clone + Copy,
+{
+    center: Point<F>,
+    radius: F,
+    #[builder(setter(into, strip_option), default)]
+    stroke: Option<F>,
+}
+
+#[derive(Clone, Copy, Debug, Builder, PositionalArgs)]
+pub struct HalfEllipse<F>
+where
+    F: Float + Clone + Copy,
+{
+    center: Point<F>,
+    radius_x: F,
+    radius_y: F,
+    #[builder(setter(into, strip_option), default)]
+    stroke: Option<F>,
+}
+
+#[derive(Clone, Copy, Debug, Builder, PositionalArgs)]
+pub struct RingArc<F>
+where
+    F: Float + Clone + Copy,
+{
+    center: Point<F>,
+    radius: F,
+    stroke: F,
+    start: F,
+    end: F,
+}
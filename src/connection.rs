@@ -0,0 +1,464 @@
+//! Box drawing glyphs for any combination of arms, described by a
+//! [`Connection`], also those Unicode has no character for.
+//!
+//! Light and heavy arms are half bars reaching past the middle as far as the
+//! arms across need, and double arms turn into each other with the inner
+//! and outer corners, the way the recipes for U+2500 to U+257F draw them.
+//!
+//! [`connection_of`] and [`char_for`] go between characters and connections,
+//! by the glyph names of those recipes.
+
+use std::ops::{AddAssign, SubAssign};
+use std::sync::OnceLock;
+
+use num::Float;
+
+use crate::commands::{
+    HorHalfBarBuilder, HorLineBuilder, HorSplitHalfBarBuilder, InnerCornerBuilder,
+    OuterCornerBuilder, VertHalfBarBuilder, VertLineBuilder, VertSplitHalfBarBuilder,
+};
+use crate::drawing_command::{Metrics, Point, Side};
+use crate::font::{Configure, Font};
+
+/// The weight of an arm of a box drawing character.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Arm {
+    #[default]
+    None,
+    Light,
+    Heavy,
+    Double,
+}
+
+impl Arm {
+    fn is_single(self) -> bool {
+        matches!(self, Arm::Light | Arm::Heavy)
+    }
+}
+
+/// The arms of a box drawing character, from the middle of the cell to each
+/// of its edges.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Connection {
+    pub up: Arm,
+    pub down: Arm,
+    pub left: Arm,
+    pub right: Arm,
+}
+
+impl Connection {
+    fn arm(&self, edge: Edge) -> Arm {
+        match edge {
+            Edge::Up => self.up,
+            Edge::Down => self.down,
+            Edge::Left => self.left,
+            Edge::Right => self.right,
+        }
+    }
+
+    fn arm_mut(&mut self, edge: Edge) -> &mut Arm {
+        match edge {
+            Edge::Up => &mut self.up,
+            Edge::Down => &mut self.down,
+            Edge::Left => &mut self.left,
+            Edge::Right => &mut self.right,
+        }
+    }
+}
+
+/// The edge of the cell an arm goes to.
+#[derive(Clone, Copy, PartialEq)]
+enum Edge {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Edge {
+    fn opposite(self) -> Edge {
+        match self {
+            Edge::Up => Edge::Down,
+            Edge::Down => Edge::Up,
+            Edge::Left => Edge::Right,
+            Edge::Right => Edge::Left,
+        }
+    }
+
+    /// The edges to either side, down or left first.
+    fn across(self) -> [Edge; 2] {
+        match self {
+            Edge::Up | Edge::Down => [Edge::Left, Edge::Right],
+            Edge::Left | Edge::Right => [Edge::Down, Edge::Up],
+        }
+    }
+
+    fn is_vertical(self) -> bool {
+        matches!(self, Edge::Up | Edge::Down)
+    }
+
+    /// The side of half bars and corners towards the edge, as in the recipes.
+    fn side(self) -> Side {
+        match self {
+            Edge::Up => Side::TopLeft,
+            Edge::Down => Side::BottomRight,
+            Edge::Left => Side::BottomLeft,
+            Edge::Right => Side::TopRight,
+        }
+    }
+
+    /// The direction of the edge from the middle, along its axis.
+    fn sign<F: Float>(self) -> F {
+        match self {
+            Edge::Up | Edge::Right => F::one(),
+            Edge::Down | Edge::Left => -F::one(),
+        }
+    }
+}
+
+/// Words of the glyph names of the box drawing recipes, for a weight or for
+/// the arms it is given to.
+const WORDS: [(&str, Option<Arm>, &[Edge]); 10] = [
+    ("light", Some(Arm::Light), &[]),
+    ("heavy", Some(Arm::Heavy), &[]),
+    ("sng", Some(Arm::Light), &[]),
+    ("dbl", Some(Arm::Double), &[]),
+    ("up", None, &[Edge::Up]),
+    ("dn", None, &[Edge::Down]),
+    ("left", None, &[Edge::Left]),
+    ("right", None, &[Edge::Right]),
+    ("vert", None, &[Edge::Up, Edge::Down]),
+    ("horz", None, &[Edge::Left, Edge::Right]),
+];
+
+/// The arms of the box drawing character `c`, read from the glyph name of
+/// its recipe, as in `dnlightrightheavybxd` or `heavyleftlightrightbxd`.
+///
+/// Only characters made of straight arms have one: the dashed lines, arcs
+/// and diagonals don't.
+pub fn connection_of(c: char) -> Option<Connection> {
+    let i = (c as u32).checked_sub(0x2500)? as usize;
+    *connections().get(i)?
+}
+
+/// The connections of U+2500 to U+257F, read from the glyph names once.
+fn connections() -> &'static [Option<Connection>; 128] {
+    static CONNECTIONS: OnceLock<[Option<Connection>; 128]> = OnceLock::new();
+    CONNECTIONS.get_or_init(|| {
+        std::array::from_fn(|i| {
+            let name = Font::<f64>::glyph_name(0x2500 + i as u32);
+            parse(&name)
+        })
+    })
+}
+
+/// The arms named in a box drawing glyph name.
+fn parse(name: &str) -> Option<Connection> {
+    let mut name = name.strip_suffix("bxd")?;
+    let mut words = Vec::new();
+    while !name.is_empty() {
+        let (word, weight, edges) = WORDS.iter().find(|(word, ..)| name.starts_with(word))?;
+        name = &name[word.len()..];
+        words.push((*weight, *edges));
+    }
+    // a weight comes after its arms, or before them when the name starts
+    // with one
+    if words.first()?.0.is_some() {
+        words.reverse();
+    }
+    let mut connection = Connection::default();
+    let mut arms = Vec::new();
+    for (weight, edges) in words {
+        match weight {
+            Some(weight) => arms
+                .drain(..)
+                .for_each(|edge| *connection.arm_mut(edge) = weight),
+            None => arms.extend_from_slice(edges),
+        }
+    }
+    arms.is_empty().then_some(connection)
+}
+
+/// How far apart the weights of two arms are: light is nearer to heavy and
+/// double than they are to each other, and any arm is far from none at all.
+fn distance(a: Arm, b: Arm) -> u32 {
+    match (a, b) {
+        _ if a == b => 0,
+        (Arm::None, _) | (_, Arm::None) => 16,
+        (Arm::Heavy, Arm::Double) | (Arm::Double, Arm::Heavy) => 2,
+        _ => 1,
+    }
+}
+
+/// The box drawing character with the arms of `connection`, or else the
+/// closest one with the same arms, in the weights nearest to them.
+///
+/// This is the reverse of [`connection_of`], and there is none for a
+/// connection without arms.
+pub fn char_for(connection: Connection) -> Option<char> {
+    if connection == Connection::default() {
+        return None;
+    }
+    let edges = [Edge::Up, Edge::Down, Edge::Left, Edge::Right];
+    ('\u{2500}'..='\u{257F}')
+        .zip(connections())
+        .filter_map(|(c, other)| Some((c, (*other)?)))
+        .min_by_key(|(_, other)| {
+            edges
+                .iter()
+                .map(|edge| distance(connection.arm(*edge), other.arm(*edge)))
+                .sum::<u32>()
+        })
+        .map(|(c, _)| c)
+}
+
+/// The width of a light or heavy `arm`.
+fn width<F: Float>(m: &Metrics<F>, arm: Arm) -> F {
+    match arm {
+        Arm::Light => m.stroke,
+        Arm::Heavy => m.stroke * m.fat,
+        _ => F::zero(),
+    }
+}
+
+/// How far the light or heavy arm towards `edge` reaches past the middle,
+/// as the butt of its half bar.
+fn single_butt<F: Float>(m: &Metrics<F>, connection: Connection, edge: Edge) -> F {
+    let across = edge.across().map(|edge| connection.arm(edge));
+    let opposite = connection.arm(edge.opposite());
+    if !across.contains(&Arm::Double) {
+        // over the widest arm across
+        across
+            .iter()
+            .fold(m.stroke, |butt, arm| butt.max(width(m, *arm)))
+    } else if across == [Arm::Double; 2] && opposite.is_single() {
+        // on to the far line of the double arms across
+        m.stroke
+    } else if across == [Arm::Double; 2] || opposite == Arm::Double {
+        // up to the near line, where the double lines go on or turn
+        -m.stroke
+    } else {
+        // over both lines of the double arm across, which end here
+        m.stroke * F::from(3).unwrap()
+    }
+}
+
+/// A light or heavy arm towards `edge`.
+fn single<F>(connection: Connection, edge: Edge) -> Configure<F>
+where
+    F: Float + AddAssign + SubAssign + 'static,
+{
+    Box::new(move |m: &Metrics<F>| {
+        let butt = single_butt(m, connection, edge);
+        let fatness = match connection.arm(edge) {
+            Arm::Heavy => m.fat,
+            _ => F::one(),
+        };
+        if edge.is_vertical() {
+            let mut builder = VertHalfBarBuilder::default();
+            builder.side(edge.side()).fatness(fatness);
+            match edge {
+                Edge::Up => builder.butt_bot(butt),
+                _ => builder.butt_top(butt),
+            };
+            builder.build().unwrap().into()
+        } else {
+            let mut builder = HorHalfBarBuilder::default();
+            builder.side(edge.side()).fatness(fatness);
+            match edge {
+                Edge::Right => builder.butt_left(butt),
+                _ => builder.butt_right(butt),
+            };
+            builder.build().unwrap().into()
+        }
+    })
+}
+
+/// A double arm towards `edge` with no double arms across, its lines ending
+/// in the middle, or past it to meet the arms across.
+fn double<F>(connection: Connection, edge: Edge) -> Vec<Configure<F>>
+where
+    F: Float + AddAssign + SubAssign + 'static,
+{
+    let across = edge.across().map(|edge| connection.arm(edge));
+    let opposite = connection.arm(edge.opposite());
+    let meets = across != [Arm::None; 2] && opposite != Arm::Double;
+    let mut commands: Vec<Configure<F>> = vec![Box::new(move |m: &Metrics<F>| {
+        if edge.is_vertical() {
+            let mut builder = VertSplitHalfBarBuilder::default();
+            builder.side(edge.side());
+            match (meets, edge) {
+                (true, Edge::Up) => builder.butt_bot(m.stroke),
+                (true, _) => builder.butt_top(m.stroke),
+                _ => &mut builder,
+            };
+            builder.build().unwrap().into()
+        } else {
+            let mut builder = HorSplitHalfBarBuilder::default();
+            builder.side(edge.side());
+            match (meets, edge) {
+                (true, Edge::Right) => builder.butt_left(m.stroke),
+                (true, _) => builder.butt_right(m.stroke),
+                _ => &mut builder,
+            };
+            builder.build().unwrap().into()
+        }
+    })];
+    if across == [Arm::None; 2] && opposite.is_single() {
+        // a bar across the ends of the lines, for the single arm to meet
+        commands.push(Box::new(move |m: &Metrics<F>| {
+            let two = F::one() + F::one();
+            let (x, y) = (m.width / two, m.median);
+            let half = m.stroke * F::from(1.5).unwrap();
+            if edge.is_vertical() {
+                let mut builder = HorLineBuilder::default();
+                builder
+                    .start(Point::new(x - half, y))
+                    .end(Point::new(x + half, y))
+                    .stroke(m.stroke)
+                    .butt_left(F::zero())
+                    .butt_right(F::zero());
+                builder.build().unwrap().into()
+            } else {
+                let mut builder = VertLineBuilder::default();
+                builder
+                    .start(Point::new(x, y - half))
+                    .end(Point::new(x, y + half))
+                    .stroke(m.stroke);
+                builder.build().unwrap().into()
+            }
+        }));
+    }
+    commands
+}
+
+/// The lines of a double arm towards `edge` where there are double arms
+/// across: each turns into the line of a double arm next to it, goes on into
+/// the opposite one, or turns around the middle into the far arm across.
+fn double_corners<F>(connection: Connection, edge: Edge) -> Vec<Configure<F>>
+where
+    F: Float + AddAssign + SubAssign + 'static,
+{
+    let mut commands: Vec<Configure<F>> = Vec::new();
+    for towards in edge.across() {
+        if connection.arm(towards) == Arm::Double {
+            // drawn once, from the horizontal arm
+            if !edge.is_vertical() {
+                commands.push(Box::new(move |_: &Metrics<F>| {
+                    let mut builder = InnerCornerBuilder::default();
+                    builder.side(edge.side()).fold(towards.side());
+                    builder.build().unwrap().into()
+                }));
+            }
+        } else if connection.arm(edge.opposite()) == Arm::Double {
+            commands.push(Box::new(move |m: &Metrics<F>| {
+                let two = F::one() + F::one();
+                let offset = m.stroke * towards.sign();
+                if edge.is_vertical() {
+                    let x = m.width / two + offset;
+                    let end = m.median + m.height / two * edge.sign();
+                    let (bottom, top) = match edge {
+                        Edge::Up => (m.median, end),
+                        _ => (end, m.median),
+                    };
+                    let mut builder = VertLineBuilder::default();
+                    builder
+                        .start(Point::new(x, bottom))
+                        .end(Point::new(x, top))
+                        .stroke(m.stroke);
+                    builder.build().unwrap().into()
+                } else {
+                    let mut builder = HorHalfBarBuilder::default();
+                    builder.side(edge.side()).median(m.median + offset);
+                    builder.build().unwrap().into()
+                }
+            }));
+        } else if !edge.is_vertical() {
+            let fold = towards.opposite().side();
+            commands.push(Box::new(move |_: &Metrics<F>| {
+                let mut builder = OuterCornerBuilder::default();
+                builder.side(edge.side()).fold(fold);
+                builder.build().unwrap().into()
+            }));
+        }
+    }
+    commands
+}
+
+/// The drawing of `connection`.
+pub(crate) fn commands<F>(connection: Connection) -> Vec<Configure<F>>
+where
+    F: Float + AddAssign + SubAssign + 'static,
+{
+    let mut commands = Vec::new();
+    for edge in [Edge::Up, Edge::Down, Edge::Left, Edge::Right] {
+        let doubles_across = edge
+            .across()
+            .iter()
+            .any(|edge| connection.arm(*edge) == Arm::Double);
+        match connection.arm(edge) {
+            Arm::None => {}
+            Arm::Light | Arm::Heavy => commands.push(single(connection, edge)),
+            Arm::Double if doubles_across => commands.extend(double_corners(connection, edge)),
+            Arm::Double => commands.extend(double(connection, edge)),
+        }
+    }
+    commands
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{char_for, connection_of, Arm, Connection};
+    use crate::drawing_command::Metrics;
+    use crate::font::Font;
+    use crate::raster::{Bitmap, PixelGrid, Rasterizer};
+
+    #[test]
+    fn connections_draw_as_the_recipes_do() {
+        let font = Font::new(Metrics::<f64>::default());
+        let metrics = Metrics::for_pixel_cell(12, 24, 2, font.metrics().fat);
+        let render = |connection: Connection| -> Bitmap {
+            let rasterizer = Rasterizer::new(&metrics, 12, 24);
+            Font::new(metrics).draw_connection(connection, PixelGrid(&rasterizer));
+            rasterizer.into_bitmap()
+        };
+        let arms = |up, down, left, right| Connection {
+            up,
+            down,
+            left,
+            right,
+        };
+        // every character of straight arms, as it is read from its recipe
+        let mut count = 0;
+        for c in '\u{2500}'..='\u{257F}' {
+            if let Some(connection) = connection_of(c) {
+                let cell = font.render_cell(c as u32, 12, 24, 2);
+                assert_eq!(render(connection).data, cell.data, "{:X}", c as u32);
+                assert_eq!(char_for(connection), Some(c));
+                count += 1;
+            }
+        }
+        assert_eq!(count, 109);
+        assert_eq!(
+            connection_of('╟'),
+            Some(arms(Arm::Double, Arm::Double, Arm::None, Arm::Light))
+        );
+
+        // heavy up and double left, which Unicode lacks, reach their edges
+        // as U+2503 and U+2550 do, and join
+        use Arm::{Double, Heavy, None};
+        let cell = render(arms(Heavy, None, Double, None));
+        let (heavy, double) = (
+            font.render_cell(0x2503, 12, 24, 2),
+            font.render_cell(0x2550, 12, 24, 2),
+        );
+        assert!((0..12).all(|x| cell.get(x, 0) == heavy.get(x, 0)));
+        assert!((0..24).all(|y| cell.get(0, y) == double.get(0, y)));
+        assert_eq!(cell.get(6, 12), 255);
+
+        // the closest characters keep the arms, in the nearest weights
+        assert_eq!(char_for(arms(None, None, None, Double)), Some('╶'));
+        assert_eq!(char_for(arms(Double, Double, Heavy, None)), Some('╢'));
+        assert_eq!(char_for(Connection::default()), Option::None);
+    }
+}
@@ -31,6 +31,17 @@ pub enum Side {
     BottomRight,
 }
 
+/// How dots, such as those of the Braille patterns, are drawn.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum DotShape {
+    #[default]
+    Round,
+    /// Squares of the same width as round dots, filling whole pixels when
+    /// rendered to a cell.
+    Square,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Metrics<F: Float> {
     /// Glyph width.
     pub width: F,
@@ -63,6 +74,78 @@ pub struct Metrics<F: Float> {
 }
 
 impl<F: Float> Metrics<F> {
+    /// Metrics for glyphs filling a cell of the given advance width, from
+    /// `descender` to `ascender`, so blocks and lines connect across lines.
+    ///
+    /// All other values keep their default proportions: stroke weight
+    /// follows the width, heights follow the cell height.
+    pub fn for_cell(width: F, ascender: F, descender: F) -> Metrics<F> {
+        let f = |v: f64| F::from(v).unwrap();
+        let block_height = ascender - descender;
+        let stroke = width * f(160. / 600.);
+        let fat = f(2.);
+        Metrics {
+            width,
+            height: block_height,
+            median: (ascender + descender) / f(2.),
+            stroke,
+            fat,
+            radius: width / f(2.),
+            block_height,
+            em_height: block_height * f(1200. / 1400.),
+            fat_stroke: stroke * fat,
+            butt: stroke,
+            kappa: f(4. * (2f64.sqrt() - 1.) / 3.),
+            block_origin: Point::new(F::zero(), descender),
+            block_top: Point::new(width, ascender),
+        }
+    }
+
+    /// Moves the median line, lengthening line elements so they still reach
+    /// both ends of the block area.
+    pub fn with_median(mut self, median: F) -> Metrics<F> {
+        let above = self.block_top.y - median;
+        let below = median - self.block_origin.y;
+        self.median = median;
+        self.height = above.max(below) * two();
+        self
+    }
+
+    /// Sets the stroke weight, along with the values derived from it.
+    pub fn with_stroke(mut self, stroke: F) -> Metrics<F> {
+        self.stroke = stroke;
+        self.fat_stroke = stroke * self.fat;
+        self.butt = stroke;
+        self
+    }
+
+    /// Metrics in pixels for a terminal cell, so that straight lines fall on
+    /// the pixel grid.
+    ///
+    /// The block area is the cell, with its origin at the bottom left. Both
+    /// stroke weights are whole pixels, the fat one being the nearest to
+    /// `stroke * fat` with the same parity, and the median sits at a pixel
+    /// centre for odd strokes and at a pixel edge for even ones.
+    pub fn for_pixel_cell(width: usize, height: usize, stroke: usize, fat: F) -> Metrics<F> {
+        let f = |v: usize| F::from(v).unwrap();
+        let stroke = stroke.max(1);
+        let fat_stroke = (f(stroke) * fat)
+            .round()
+            .to_usize()
+            .unwrap_or(stroke)
+            .max(stroke);
+        let fat_stroke = fat_stroke + (fat_stroke - stroke) % 2;
+        // the bar sits at the middle, the odd pixel left over going on top
+        let median = f(height.saturating_sub(stroke) / 2) + f(stroke) / two();
+
+        let mut metrics = Metrics::for_cell(f(width), f(height), F::zero())
+            .with_median(median)
+            .with_stroke(f(stroke));
+        metrics.fat = f(fat_stroke) / f(stroke);
+        metrics.fat_stroke = f(fat_stroke);
+        metrics
+    }
+
     fn set_width(&mut self, width: F) {
         self.width = width;
     }
@@ -204,6 +287,34 @@ impl<F: Float> Hash for Point<F> {
     }
 }
 
+/// Hashes every value rounded to thousandths of a unit, like [`Point`], so
+/// that glyphs rendered from the metrics can be cached by them.
+impl<F: Float> Hash for Metrics<F> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        for v in [
+            self.width,
+            self.height,
+            self.median,
+            self.stroke,
+            self.fat,
+            self.radius,
+            self.block_height,
+            self.em_height,
+            self.fat_stroke,
+            self.butt,
+            self.kappa,
+        ] {
+            (v * F::from(1000f32).unwrap())
+                .round()
+                .to_i64()
+                .unwrap()
+                .hash(state);
+        }
+        self.block_origin.hash(state);
+        self.block_top.hash(state);
+    }
+}
+
 impl<F> Point<F>
 where
     F: Float + Clone + Copy,
@@ -235,6 +346,19 @@ fn dedup<T: PartialEq>(s: &[T]) -> usize {
     c
 }
 
+/// Variation on `range()`, since step values for dashed lines may be floats.
+fn float_range<F: Float + AddAssign>(mut x: F, y: F, step: F) -> impl Iterator<Item = F> {
+    std::iter::from_fn(move || {
+        if x < y {
+            let current = x;
+            x += step;
+            Some(current)
+        } else {
+            None
+        }
+    })
+}
+
 #[inline]
 pub(crate) fn two<F: Float>() -> F {
     F::one() + F::one()
@@ -267,14 +391,30 @@ pub trait Canvas<F: Float> {
     fn close_path(&self);
 }
 
+impl<F: Float, C: Canvas<F> + ?Sized> Canvas<F> for &C {
+    fn move_to(&self, pt: &Point<F>) {
+        (**self).move_to(pt)
+    }
+
+    fn line_to(&self, pt: &Point<F>) {
+        (**self).line_to(pt)
+    }
+
+    fn curve_to(&self, pt1: &Point<F>, pt2: &Point<F>, ptend: &Point<F>) {
+        (**self).curve_to(pt1, pt2, ptend)
+    }
+
+    fn close_path(&self) {
+        (**self).close_path()
+    }
+}
+
 pub struct DrawingCommand<'m, C: Canvas<F>, F: Float> {
     pub(crate) metrics: &'m Metrics<F>,
     pub(crate) canvas: C,
 }
 
-impl<'m, C: Canvas<F>, F: Float + AddAssign + CheckedAdd + Mul + SubAssign>
-    DrawingCommand<'m, C, F>
-{
+impl<'m, C: Canvas<F>, F: Float + AddAssign + Mul + SubAssign> DrawingCommand<'m, C, F> {
     /// General drawing function for a rectangle.
     fn rectangle(
         &self,
@@ -290,8 +430,8 @@ impl<'m, C: Canvas<F>, F: Float + AddAssign + CheckedAdd + Mul + SubAssign>
         self.canvas.close_path();
     }
 
-    // General drawing function for a polygon.
-    fn polygon(&self, coords: &[Point<F>]) {
+    /// General drawing function for a polygon.
+    pub fn polygon(&self, coords: &[Point<F>]) {
         if dedup(coords) >= 3 {
             self.canvas.move_to(&coords[0]);
             for (point_index, point_coords) in coords.iter().enumerate().skip(1) {
@@ -395,7 +535,7 @@ impl<'m, C: Canvas<F>, F: Float + AddAssign + CheckedAdd + Mul + SubAssign>
         let stroke = stroke.into().unwrap_or(self.metrics.stroke);
         let step_length = width / step;
         let gap = step_length / step;
-        for w in range_step(zero(), width, step_length) {
+        for w in float_range(zero(), width, step_length) {
             if w + step_length - gap < width {
                 let w = w + gap / two(); // centering the dashed line in the glyph
                 self.hor_line(
@@ -422,7 +562,7 @@ impl<'m, C: Canvas<F>, F: Float + AddAssign + CheckedAdd + Mul + SubAssign>
         let gap = step_length / step;
         let top = self.metrics.median + self.metrics.em_height / two();
 
-        for h in range_step(
+        for h in float_range(
             self.metrics.median - length / two(),
             self.metrics.median + length / two(),
             step_length,
@@ -441,7 +581,7 @@ impl<'m, C: Canvas<F>, F: Float + AddAssign + CheckedAdd + Mul + SubAssign>
     }
 
     /// A dot.
-    fn dot(&self, center: &Point<F>, radius: F) {
+    pub fn dot(&self, center: &Point<F>, radius: F) {
         let Point { x, y } = *center;
 
         self.canvas.move_to(&(x - radius, y).into());
@@ -468,6 +608,124 @@ impl<'m, C: Canvas<F>, F: Float + AddAssign + CheckedAdd + Mul + SubAssign>
         self.canvas.close_path();
     }
 
+    /// A circle `stroke` thick, its outside `radius` from `center`.
+    pub fn ring(&self, center: &Point<F>, radius: F, stroke: F) {
+        self.dot(center, radius);
+
+        // the hole, clockwise
+        let Point { x, y } = *center;
+        let radius = radius - stroke;
+        let kappa = self.metrics.kappa;
+        self.canvas.move_to(&(x - radius, y).into());
+        self.canvas.curve_to(
+            &(x - radius, y + radius * kappa).into(),
+            &(x - radius * kappa, y + radius).into(),
+            &(x, y + radius).into(),
+        );
+        self.canvas.curve_to(
+            &(x + radius * kappa, y + radius).into(),
+            &(x + radius, y + radius * kappa).into(),
+            &(x + radius, y).into(),
+        );
+        self.canvas.curve_to(
+            &(x + radius, y - radius * kappa).into(),
+            &(x + radius * kappa, y - radius).into(),
+            &(x, y - radius).into(),
+        );
+        self.canvas.curve_to(
+            &(x - radius * kappa, y - radius).into(),
+            &(x - radius, y - radius * kappa).into(),
+            &(x - radius, y).into(),
+        );
+        self.canvas.close_path();
+    }
+
+    /// The part of a [`ring`](Self::ring) from angle `start` to `end`
+    /// counter-clockwise, in radians from the right, with square ends.
+    pub fn ring_arc(&self, center: &Point<F>, radius: F, stroke: F, start: F, end: F) {
+        // cubic curves of at most a quarter circle each, with handles
+        // scaled so that a quarter circle uses `kappa` as `dot` does
+        let quarter = F::from(std::f64::consts::FRAC_PI_2).unwrap();
+        let count = ((end - start) / quarter).ceil().max(one());
+        let step = (end - start) / count;
+        let four = F::from(4).unwrap();
+        let handle = self.metrics.kappa * (step / four).tan() / (quarter / four).tan();
+        let point = |radius: F, angle: F| -> Point<F> {
+            (
+                center.x + radius * angle.cos(),
+                center.y + radius * angle.sin(),
+            )
+                .into()
+        };
+        // from `from` to `to` along the circle, either way round
+        let curve = |radius: F, from: F, to: F| {
+            let length = handle * radius * (to - from).signum();
+            self.canvas.curve_to(
+                &(
+                    center.x + radius * from.cos() - length * from.sin(),
+                    center.y + radius * from.sin() + length * from.cos(),
+                )
+                    .into(),
+                &(
+                    center.x + radius * to.cos() + length * to.sin(),
+                    center.y + radius * to.sin() - length * to.cos(),
+                )
+                    .into(),
+                &point(radius, to),
+            );
+        };
+
+        let inner = radius - stroke;
+        let count = count.to_usize().unwrap();
+        let angle = |i: usize| start + step * F::from(i).unwrap();
+        self.canvas.move_to(&point(radius, start));
+        for i in 0..count {
+            curve(radius, angle(i), angle(i + 1));
+        }
+        self.canvas.line_to(&point(inner, end));
+        for i in (0..count).rev() {
+            curve(inner, angle(i + 1), angle(i));
+        }
+        self.canvas.close_path();
+    }
+
+    /// Half an ellipse around `center`, the right half, or the left one when
+    /// `radius_x` is negative. Filled, or only its curve `stroke` thick.
+    pub fn half_ellipse(&self, center: &Point<F>, radius_x: F, radius_y: F, stroke: Option<F>) {
+        let Point { x, y } = *center;
+        let kappa = self.metrics.kappa;
+        // the left half is the right one turned around the center, which
+        // keeps the contour counter-clockwise
+        let (rx, ry) = (radius_x, radius_y * radius_x.signum());
+
+        self.canvas.move_to(&(x, y - ry).into());
+        self.canvas.curve_to(
+            &(x + rx * kappa, y - ry).into(),
+            &(x + rx, y - ry * kappa).into(),
+            &(x + rx, y).into(),
+        );
+        self.canvas.curve_to(
+            &(x + rx, y + ry * kappa).into(),
+            &(x + rx * kappa, y + ry).into(),
+            &(x, y + ry).into(),
+        );
+        if let Some(stroke) = stroke {
+            let (rx, ry) = (rx - stroke * rx.signum(), ry - stroke * ry.signum());
+            self.canvas.line_to(&(x, y + ry).into());
+            self.canvas.curve_to(
+                &(x + rx * kappa, y + ry).into(),
+                &(x + rx, y + ry * kappa).into(),
+                &(x + rx, y).into(),
+            );
+            self.canvas.curve_to(
+                &(x + rx, y - ry * kappa).into(),
+                &(x + rx * kappa, y - ry).into(),
+                &(x, y - ry).into(),
+            );
+        }
+        self.canvas.close_path();
+    }
+
     /// Shading patterns, consisting of polka dots.
     /// Not used in any of the drawing recipes, but perhaps useful for somebody.
     pub fn polka_shade(&self, shade: Shade) {
@@ -479,8 +737,8 @@ impl<'m, C: Canvas<F>, F: Float + AddAssign + CheckedAdd + Mul + SubAssign>
             Shade::SeventyFive => F::from(54f32).unwrap(),
         };
 
-        for w in range_step(zero(), self.metrics.width, hstep) {
-            for h in range_step(
+        for w in float_range(zero(), self.metrics.width, hstep) {
+            for h in float_range(
                 (self.metrics.median - self.metrics.block_height / two()).round(),
                 (self.metrics.median + self.metrics.block_height / two()).round(),
                 vstep * two(),
@@ -506,8 +764,8 @@ impl<'m, C: Canvas<F>, F: Float + AddAssign + CheckedAdd + Mul + SubAssign>
             Shade::SeventyFive => (F::from(45f32).unwrap(), F::from(70f32).unwrap()),
         };
 
-        for w in range_step(zero(), self.metrics.width, hstep) {
-            for h in range_step(
+        for w in float_range(zero(), self.metrics.width, hstep) {
+            for h in float_range(
                 self.metrics.median - self.metrics.block_height / two(),
                 self.metrics.median + self.metrics.block_height / two(),
                 vstep * two(),
@@ -543,7 +801,7 @@ impl<'m, C: Canvas<F>, F: Float + AddAssign + CheckedAdd + Mul + SubAssign>
 
         // leftmost point:
         let leftmost_x = F::zero() - Float::cos(angle) * hypotenuse - stroke;
-        let xvalues: Vec<_> = range_step(leftmost_x, self.metrics.width + stroke, step)
+        let xvalues: Vec<_> = float_range(leftmost_x, self.metrics.width + stroke, step)
             .map(|xvalue| (xvalue, xvalue + stroke))
             .collect();
 
@@ -619,7 +877,7 @@ impl<'m, C: Canvas<F>, F: Float + AddAssign + CheckedAdd + Mul + SubAssign>
         };
         let stroke = self.metrics.width / cast(30f32).unwrap();
 
-        for xvalue in range_step(zero(), self.metrics.width, step) {
+        for xvalue in float_range(zero(), self.metrics.width, step) {
             let y_bot = self.metrics.median - self.metrics.height / two();
             let y_top = y_bot + self.metrics.height;
             let x_left = xvalue;
@@ -737,20 +995,39 @@ impl<'m, C: Canvas<F>, F: Float + AddAssign + CheckedAdd + Mul + SubAssign>
         );
         let oaend = (c_start_x - (stroke / two() * xflip), c_start_y);
 
-        self.draw_arc(
-            &start1.into(),
-            &start2.into(),
-            &end1.into(),
-            &end2.into(),
-            &iastart.into(),
-            &iapoint1.into(),
-            &iapoint2.into(),
-            &iaend.into(),
-            &oastart.into(),
-            &oapoint1.into(),
-            &oapoint2.into(),
-            &oaend.into(),
-        );
+        if xflip == yflip {
+            self.draw_arc(
+                &start1.into(),
+                &start2.into(),
+                &end1.into(),
+                &end2.into(),
+                &iastart.into(),
+                &iapoint1.into(),
+                &iapoint2.into(),
+                &iaend.into(),
+                &oastart.into(),
+                &oapoint1.into(),
+                &oapoint2.into(),
+                &oaend.into(),
+            );
+        } else {
+            // mirrored once, the same points run clockwise; going the other
+            // way round keeps overlapping lines filled
+            self.draw_arc(
+                &start2.into(),
+                &start1.into(),
+                &end2.into(),
+                &end1.into(),
+                &oaend.into(),
+                &oapoint2.into(),
+                &oapoint1.into(),
+                &oastart.into(),
+                &iaend.into(),
+                &iapoint2.into(),
+                &iapoint1.into(),
+                &iastart.into(),
+            );
+        }
     }
 
     /// Horizontal bar.
@@ -928,7 +1205,7 @@ impl<'m, C: Canvas<F>, F: Float + AddAssign + CheckedAdd + Mul + SubAssign>
         self.vert_line(
             &(leftx, self.metrics.median - self.metrics.height / two()).into(),
             &(leftx, self.metrics.median + self.metrics.height / two()).into(),
-            self.metrics.height * fatness,
+            self.metrics.stroke * fatness,
             butt_bot,
             butt_top,
         );
@@ -1022,10 +1299,10 @@ impl<'m, C: Canvas<F>, F: Float + AddAssign + CheckedAdd + Mul + SubAssign>
         let fatness = fatness.into().unwrap_or(one());
         let mut corner_median = corner_median.into().unwrap_or(self.metrics.median);
 
-        if matches!(side, Side::TopLeft | Side::TopRight) {
-            corner_median = corner_median - self.metrics.stroke * fatness;
+        if matches!(fold, Side::TopLeft | Side::TopRight) {
+            corner_median -= self.metrics.stroke * fatness;
         }
-        if matches!(side, Side::BottomLeft | Side::BottomRight) {
+        if matches!(fold, Side::BottomLeft | Side::BottomRight) {
             corner_median += self.metrics.stroke * fatness;
         }
         let x = match side {
@@ -1090,7 +1367,7 @@ impl<'m, C: Canvas<F>, F: Float + AddAssign + CheckedAdd + Mul + SubAssign>
         let fatness = fatness.into().unwrap_or(one());
         let mut corner_median = corner_median.into().unwrap_or(self.metrics.median);
 
-        if matches!(side, Side::TopLeft | Side::TopRight) {
+        if matches!(fold, Side::TopLeft | Side::TopRight) {
             corner_median += self.metrics.stroke * fatness;
         } else {
             // bottom
@@ -1159,3 +1436,43 @@ fn shift_coords<F: Float + AddAssign>(
         pt.y += y_shift;
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use super::*;
+    use crate::outline::{OutlineBuilder, PathSegment};
+
+    #[test]
+    fn ring_arc_follows_the_circle() {
+        let metrics = Metrics::<f64>::default();
+        let builder = OutlineBuilder::new();
+        let drawing = DrawingCommand {
+            metrics: &metrics,
+            canvas: &builder,
+        };
+        // the upper half of a ring 20 thick, around (300, 300)
+        drawing.ring_arc(&Point::new(300., 300.), 100., 20., 0., PI);
+        let outline = builder.into_outline();
+
+        assert_eq!(outline.contours().len(), 1);
+        let contour = &outline.contours()[0];
+        assert!(contour.is_closed());
+        assert_eq!(contour.start(), Point::new(400., 300.));
+        // on-curve points lie on the outer or inner circle, the square ends
+        // on the horizontal through the center
+        for end in contour.iter().filter_map(PathSegment::end) {
+            let distance = (end.x - 300.).hypot(end.y - 300.);
+            assert!((distance - 100.).abs() < 1e-9 || (distance - 80.).abs() < 1e-9);
+            assert!(end.y > 300. - 1e-9);
+        }
+        let bounds = contour.bounds();
+        assert!((bounds.min.x - 200.).abs() < 1e-9 && (bounds.max.x - 400.).abs() < 1e-9);
+        assert!((bounds.max.y - 400.).abs() < 0.1);
+        // counter-clockwise, with the area of half the ring, within what
+        // `area` loses by flattening the curves
+        let half_ring = PI * (100. * 100. - 80. * 80.) / 2.;
+        assert!((contour.area() - half_ring).abs() < half_ring / 100.);
+    }
+}
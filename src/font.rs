@@ -1,19 +1,113 @@
 #![allow(dead_code)]
 
-use std::ops::{AddAssign, SubAssign};
-
-use num::CheckedAdd;
+use std::borrow::Cow;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::{AddAssign, RangeInclusive, SubAssign};
 
+use crate::braille;
+use crate::branch;
 use crate::commands::*;
-use crate::drawing_command::{Canvas, Direction, DrawingCommand, Metrics, Point, Shade, Side};
+use crate::connection::{self, Connection};
+use crate::drawing_command::{
+    Canvas, Direction, DotShape, DrawingCommand, Metrics, Point, Shade, Side,
+};
+use crate::geometric;
+use crate::legacy;
+use crate::outline::{Outline, OutlineBuilder};
+use crate::powerline;
+use crate::progress;
+use crate::raster::{Bitmap, PixelGrid, Rasterizer};
+use crate::sfnt::TrueTypeWriter;
+use crate::svg::{self, SvgCanvas};
+use crate::technical;
+
+/// Code points with a recipe.
+const RANGES: [RangeInclusive<u32>; 15] = [
+    0x2320..=0x2321,   // Miscellaneous Technical: integral pieces
+    0x239B..=0x23AE,   // Miscellaneous Technical: bracket pieces
+    0x23BA..=0x23BD,   // Miscellaneous Technical: scan lines
+    0x2500..=0x259F,   // Box Drawing, Block Elements
+    0x25A0..=0x25A1,   // Geometric Shapes: squares
+    0x25AA..=0x25AF,   // Geometric Shapes: small squares, rectangles
+    0x25B2..=0x25C5,   // Geometric Shapes: triangles
+    0x25CB..=0x25CB,   // Geometric Shapes: white circle
+    0x25CF..=0x25CF,   // Geometric Shapes: black circle
+    0x25DC..=0x25E5,   // Geometric Shapes: circle pieces, corner triangles
+    0x25EF..=0x25EF,   // Geometric Shapes: large circle
+    0x25F8..=0x25FF,   // Geometric Shapes: corner triangles, medium squares
+    0x2800..=0x28FF,   // Braille Patterns
+    0x1CD00..=0x1CDE5, // Symbols for Legacy Computing Supplement: octants
+    0x1FB00..=0x1FB8B, // Symbols for Legacy Computing: sextants, wedges, eighths
+];
+
+/// Private use ranges with a recipe, only drawn when asked for with
+/// [`Font::private_use`] since their meaning depends on the font.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PrivateUse {
+    /// U+E0B0 to U+E0B7, the Powerline triangles and semicircles.
+    Powerline,
+    /// U+EE00 to U+EE0B, the progress bar and spinner of Fira Code.
+    ProgressBar,
+    /// U+F5D0 to U+F60D, the branch drawing symbols of kitty, for git graphs.
+    GitBranch,
+}
+
+impl PrivateUse {
+    fn range(self) -> RangeInclusive<u32> {
+        match self {
+            PrivateUse::Powerline => 0xE0B0..=0xE0B7,
+            PrivateUse::ProgressBar => 0xEE00..=0xEE0B,
+            PrivateUse::GitBranch => 0xF5D0..=0xF60D,
+        }
+    }
+}
+
+/// Returned by the writers when asked for code points they can't write.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CodePointError {
+    /// The font has no glyph for the code point.
+    NoGlyph(u32),
+    /// More code points than the format holds, with the most it holds.
+    TooMany(usize, usize),
+}
+
+impl fmt::Display for CodePointError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CodePointError::NoGlyph(c) => write!(f, "no glyph for U+{:04X}", c),
+            CodePointError::TooMany(count, max) => {
+                write!(f, "{} code points, but at most {} fit", count, max)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CodePointError {}
+
+/// Configures one drawing command from the metrics.
+pub(crate) type Configure<F> = Box<dyn Fn(&Metrics<F>) -> Commands<F> + Send + Sync>;
 
 pub struct Recipe<F: num::Float + 'static> {
     c: u32,
-    name: &'static str,
-    commands: Box<[Box<dyn Fn(&Metrics<F>) -> Commands<F> + Send + Sync>]>,
+    name: Cow<'static, str>,
+    commands: Box<[Configure<F>]>,
 }
 
-impl<F: num::Float + AddAssign + CheckedAdd + SubAssign + 'static> Recipe<F> {
+impl<F: num::Float + AddAssign + SubAssign + 'static> Recipe<F> {
+    pub(crate) fn new(
+        c: u32,
+        name: impl Into<Cow<'static, str>>,
+        commands: Vec<Configure<F>>,
+    ) -> Self {
+        Recipe {
+            c,
+            name: name.into(),
+            commands: commands.into_boxed_slice(),
+        }
+    }
+
     pub fn execute<C: Canvas<F>>(&self, ctx: &DrawingCommand<C, F>) {
         self.commands
             .iter()
@@ -47,29 +141,195 @@ macro_rules! command {
 
 pub struct Font<F: num::Float> {
     metrics: Metrics<F>,
+    dot_shape: DotShape,
+    private_use: BTreeSet<PrivateUse>,
 }
 
-impl<F: num::Float + Default + AddAssign + CheckedAdd + SubAssign + 'static> Font<F> {
+/// Hashes the metrics, rounded as [`Metrics`] hashes them, the dot shape and
+/// the private use ranges, everything the glyphs depend on.
+impl<F: num::Float> Hash for Font<F> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.metrics.hash(state);
+        self.dot_shape.hash(state);
+        self.private_use.hash(state);
+    }
+}
+
+impl<F: num::Float + Default + AddAssign + SubAssign + 'static> Font<F> {
     pub fn new(metrics: Metrics<F>) -> Font<F> {
-        Font { metrics }
+        Font {
+            metrics,
+            dot_shape: DotShape::default(),
+            private_use: BTreeSet::new(),
+        }
+    }
+
+    /// Shape of the dots of the Braille patterns, round by default.
+    pub fn dot_shape(mut self, dot_shape: DotShape) -> Self {
+        self.dot_shape = dot_shape;
+        self
     }
 
+    /// Adds the glyphs of a private use range.
+    pub fn private_use(mut self, range: PrivateUse) -> Self {
+        self.private_use.insert(range);
+        self
+    }
+
+    pub fn metrics(&self) -> &Metrics<F> {
+        &self.metrics
+    }
+
+    /// Whether there is a recipe for `c` in the ranges every font draws,
+    /// leaving out the private use ranges, see [`Font::has_glyph`].
     pub fn contains(c: u32) -> bool {
-        match c {
-            0x2500..=0x259F => true,
-            _ => false,
+        RANGES.iter().any(|range| range.contains(&c))
+    }
+
+    /// Whether this font draws `c`, also counting the private use ranges it
+    /// was asked for.
+    pub fn has_glyph(&self, c: u32) -> bool {
+        self.ranges().any(|range| range.contains(&c))
+    }
+
+    /// All code points of this font, in ascending order.
+    pub fn code_points(&self) -> impl Iterator<Item = u32> {
+        let mut ranges: Vec<_> = self.ranges().collect();
+        ranges.sort_by_key(|range| *range.start());
+        ranges.into_iter().flatten()
+    }
+
+    /// The code points the writers include unless given others: the box
+    /// drawing and block elements, U+2500 to U+259F, and the private use
+    /// ranges asked for.
+    pub fn default_code_points(&self) -> impl Iterator<Item = u32> + '_ {
+        (0x2500..=0x259F).chain(self.private_use.iter().flat_map(|range| range.range()))
+    }
+
+    /// `code_points` as given, if the font has a glyph for all of them.
+    pub(crate) fn select(
+        &self,
+        code_points: impl IntoIterator<Item = u32>,
+    ) -> Result<Vec<u32>, CodePointError> {
+        let code_points: Vec<u32> = code_points.into_iter().collect();
+        match code_points.iter().find(|c| !self.has_glyph(**c)) {
+            Some(c) => Err(CodePointError::NoGlyph(*c)),
+            None => Ok(code_points),
         }
     }
 
+    fn ranges(&self) -> impl Iterator<Item = RangeInclusive<u32>> + '_ {
+        RANGES
+            .iter()
+            .cloned()
+            .chain(self.private_use.iter().map(|range| range.range()))
+    }
+
+    /// The PostScript name of a glyph: the names of the original script for
+    /// the box drawing and block elements, common names such as `dots1245`,
+    /// `sextant136` or `integraltp` where there are some, and `uniXXXX` or
+    /// `uXXXXX` for the rest.
+    pub fn glyph_name(c: u32) -> Cow<'static, str> {
+        Font::<F>::recipe(c, DotShape::default()).name
+    }
+
     pub fn draw_to<C: Canvas<F>>(&self, c: u32, canvas: C) {
         let drawing = DrawingCommand {
             metrics: &self.metrics,
             canvas,
         };
-        Font::recipe(c).execute(&drawing);
+        Font::recipe(c, self.dot_shape).execute(&drawing);
     }
 
-    fn recipe(c: u32) -> Recipe<F> {
+    /// Draws the box drawing glyph with the arms of `connection`, also where
+    /// Unicode has no character with them.
+    pub fn draw_connection<C: Canvas<F>>(&self, connection: Connection, canvas: C) {
+        let drawing = DrawingCommand {
+            metrics: &self.metrics,
+            canvas,
+        };
+        connection::commands(connection)
+            .iter()
+            .for_each(|configure| configure(&self.metrics).execute(&drawing));
+    }
+
+    /// Collects the drawing of a glyph into an owned outline.
+    pub fn outline(&self, c: u32) -> Outline<F> {
+        let builder = OutlineBuilder::new();
+        self.draw_to(c, &builder);
+        builder.into_outline()
+    }
+
+    /// Renders a glyph as a standalone SVG document.
+    pub fn to_svg(&self, c: u32) -> String {
+        let canvas = SvgCanvas::new();
+        self.draw_to(c, &canvas);
+        svg::document(&self.metrics, &canvas.into_path_data())
+    }
+
+    /// Builds a TrueType font file of the default code points, see
+    /// [`TrueTypeWriter`].
+    pub fn to_ttf(&self, family_name: &str) -> Vec<u8> {
+        TrueTypeWriter::new(self, family_name).write()
+    }
+
+    /// Renders a glyph into an anti-aliased coverage bitmap of the given cell size.
+    pub fn rasterize(&self, c: u32, width: usize, height: usize) -> Bitmap {
+        let rasterizer = Rasterizer::new(&self.metrics, width, height);
+        self.draw_to(c, &rasterizer);
+        rasterizer.into_bitmap()
+    }
+
+    /// Renders a glyph for a terminal cell of `cell_w` by `cell_h` pixels,
+    /// with light lines `thickness` pixels wide.
+    ///
+    /// The glyph is drawn with [`Metrics::for_pixel_cell`], keeping the fat
+    /// factor of this font, and snapped to the pixel grid, so straight lines
+    /// and blocks have no blurry edges and connect across cells.
+    pub fn render_cell(&self, c: u32, cell_w: usize, cell_h: usize, thickness: usize) -> Bitmap {
+        let metrics = Metrics::for_pixel_cell(cell_w, cell_h, thickness, self.metrics.fat);
+        let rasterizer = Rasterizer::new(&metrics, cell_w, cell_h);
+        Font::new(metrics)
+            .dot_shape(self.dot_shape)
+            .draw_to(c, PixelGrid(&rasterizer));
+        rasterizer.into_bitmap()
+    }
+
+    /// Width of light lines in pixels for cells `cell_w` wide, in proportion
+    /// with the metrics.
+    pub fn cell_thickness(&self, cell_w: usize) -> usize {
+        let thickness = self.metrics.stroke / self.metrics.width * F::from(cell_w).unwrap();
+        thickness.round().to_usize().unwrap_or(1).max(1)
+    }
+
+    /// Pixels of a cell `cell_h` high below the baseline, in proportion with
+    /// the block area.
+    pub fn cell_descent(&self, cell_h: usize) -> usize {
+        let descent = -self.metrics.block_origin.y / self.metrics.block_height;
+        (descent * F::from(cell_h).unwrap())
+            .round()
+            .to_usize()
+            .unwrap_or(0)
+            .min(cell_h)
+    }
+
+    fn recipe(c: u32, dot_shape: DotShape) -> Recipe<F> {
+        match c {
+            0x2320..=0x2321 | 0x239B..=0x23AE | 0x23BA..=0x23BD => technical::piece(c),
+            0x25A0..=0x25FF => geometric::shape(c),
+            0x2800..=0x28FF => braille::pattern(c, dot_shape),
+            0xE0B0..=0xE0B7 => powerline::separator(c),
+            0xEE00..=0xEE0B => progress::glyph(c),
+            0xF5D0..=0xF60D => branch::symbol(c),
+            0x1CD00..=0x1CDE5 => legacy::octant(c),
+            0x1FB00..=0x1FB3B => legacy::sextant(c),
+            0x1FB3C..=0x1FB6F => legacy::wedge(c),
+            0x1FB70..=0x1FB8B => legacy::eighth(c),
+            _ => Font::box_drawing_recipe(c),
+        }
+    }
+
+    fn box_drawing_recipe(c: u32) -> Recipe<F> {
         include!("recipes.rs.in")
     }
 
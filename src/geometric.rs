@@ -0,0 +1,225 @@
+//! Recipes for part of Geometric Shapes, U+25A0 to U+25FF: the squares and
+//! rectangles, the black and white triangles, circles and quadrant circle
+//! pieces.
+//!
+//! Shapes are sized in eighths of the largest square in the block area and
+//! centred in it, so that they line up with the block elements. White
+//! shapes are outlines a light stroke thick.
+
+use std::ops::{AddAssign, SubAssign};
+
+use num::Float;
+
+use crate::commands::{DotBuilder, PolygonBuilder, RingArcBuilder};
+use crate::drawing_command::{Metrics, Point};
+use crate::font::{Configure, Recipe};
+
+/// Corners of the square, counter-clockwise from the bottom left.
+const SQUARE: [(f64, f64); 4] = [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)];
+
+/// Corners of the up pointing triangle.
+const TRIANGLE: [(f64, f64); 3] = [(-1.0, -1.0), (1.0, -1.0), (0.0, 1.0)];
+
+/// Corners of the triangle in the lower right half of the square.
+const CORNER: [(f64, f64); 3] = [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0)];
+
+/// Centre of the block area and an eighth of the largest square in it.
+fn grid<F: Float>(m: &Metrics<F>) -> (Point<F>, F) {
+    let two = F::one() + F::one();
+    let width = m.block_top.x - m.block_origin.x;
+    (
+        Point::new(
+            (m.block_origin.x + m.block_top.x) / two,
+            (m.block_origin.y + m.block_top.y) / two,
+        ),
+        width.min(m.block_height) / F::from(8).unwrap(),
+    )
+}
+
+/// The corners of a convex polygon, counter-clockwise, moved `stroke` in
+/// along both of their sides.
+fn inset<F: Float>(points: &[Point<F>], stroke: F) -> Vec<Point<F>> {
+    let n = points.len();
+    let cross = |a: (F, F), b: (F, F)| a.0 * b.1 - a.1 * b.0;
+    // each side moved in, as a point on it and its direction
+    let sides: Vec<_> = (0..n)
+        .map(|i| {
+            let (p, q) = (points[i], points[(i + 1) % n]);
+            let (dx, dy) = (q.x - p.x, q.y - p.y);
+            let length = dx.hypot(dy);
+            let (x, y) = (-dy * stroke / length, dx * stroke / length);
+            (Point::new(p.x + x, p.y + y), (dx, dy))
+        })
+        .collect();
+    (0..n)
+        .map(|i| {
+            let (a, da) = sides[(i + n - 1) % n];
+            let (b, db) = sides[i];
+            let t = cross((b.x - a.x, b.y - a.y), db) / cross(da, db);
+            Point::new(a.x + da.0 * t, a.y + da.1 * t)
+        })
+        .collect()
+}
+
+/// A polygon with `corners` scaled to `size` eighths across and along and
+/// turned `quarters` counter-clockwise, filled or, when `white`, outlined.
+fn polygon<F>(
+    corners: &'static [(f64, f64)],
+    size: (f64, f64),
+    quarters: u32,
+    white: bool,
+) -> Vec<Configure<F>>
+where
+    F: Float + AddAssign + SubAssign + 'static,
+{
+    let points = move |m: &Metrics<F>| -> Vec<Point<F>> {
+        let (center, eighth) = grid(m);
+        corners
+            .iter()
+            .map(|(x, y)| {
+                let (mut x, mut y) = (x * size.0 / 2.0, y * size.1 / 2.0);
+                for _ in 0..quarters {
+                    (x, y) = (-y, x);
+                }
+                let f = |v: f64| F::from(v).unwrap() * eighth;
+                Point::new(center.x + f(x), center.y + f(y))
+            })
+            .collect()
+    };
+    let mut commands: Vec<Configure<F>> = vec![Box::new(move |m: &Metrics<F>| {
+        let mut builder = PolygonBuilder::default();
+        builder.points(points(m));
+        builder.build().unwrap().into()
+    })];
+    if white {
+        // the hole, clockwise
+        commands.push(Box::new(move |m: &Metrics<F>| {
+            let mut hole = inset(&points(m), m.stroke);
+            hole.reverse();
+            let mut builder = PolygonBuilder::default();
+            builder.points(hole);
+            builder.build().unwrap().into()
+        }));
+    }
+    commands
+}
+
+/// A circle `size` eighths across, filled or, when `white`, outlined.
+fn circle<F>(size: u32, white: bool) -> Configure<F>
+where
+    F: Float + AddAssign + SubAssign + 'static,
+{
+    Box::new(move |m: &Metrics<F>| {
+        let (center, eighth) = grid(m);
+        let mut builder = DotBuilder::default();
+        builder
+            .center(center)
+            .radius(eighth * F::from(size).unwrap() / (F::one() + F::one()));
+        if white {
+            builder.stroke(m.stroke);
+        }
+        builder.build().unwrap().into()
+    })
+}
+
+/// The part of the white circle from `start` to `end` quarters
+/// counter-clockwise from the right.
+fn circle_arc<F>(start: u32, end: u32) -> Configure<F>
+where
+    F: Float + AddAssign + SubAssign + 'static,
+{
+    Box::new(move |m: &Metrics<F>| {
+        let (center, eighth) = grid(m);
+        let quarter = F::from(std::f64::consts::FRAC_PI_2).unwrap();
+        let mut builder = RingArcBuilder::default();
+        builder
+            .center(center)
+            .radius(eighth * F::from(3).unwrap())
+            .stroke(m.stroke)
+            .start(quarter * F::from(start).unwrap())
+            .end(quarter * F::from(end).unwrap());
+        builder.build().unwrap().into()
+    })
+}
+
+/// The geometric shape `c`.
+pub(crate) fn shape<F>(c: u32) -> Recipe<F>
+where
+    F: Float + AddAssign + SubAssign + 'static,
+{
+    let white = c & 1 == 1;
+    let commands = match c {
+        0x25A0 | 0x25A1 => polygon(&SQUARE, (6.0, 6.0), 0, white),
+        0x25AA | 0x25AB => polygon(&SQUARE, (3.0, 3.0), 0, white),
+        0x25AC | 0x25AD => polygon(&SQUARE, (8.0, 4.0), 0, white),
+        0x25AE | 0x25AF => polygon(&SQUARE, (4.0, 8.0), 0, white),
+        0x25FB | 0x25FC => polygon(&SQUARE, (5.0, 5.0), 0, white),
+        0x25FD | 0x25FE => polygon(&SQUARE, (4.0, 4.0), 0, white),
+        0x25B2..=0x25C5 => {
+            // up, right, down and left, each as black and white, then small,
+            // then as a pointer but for up and down
+            let (quarters, i) = match c - 0x25B2 {
+                i @ 0..=3 => (0, i),
+                i @ 4..=9 => (3, i - 4),
+                i @ 10..=13 => (2, i - 10),
+                i => (1, i - 14),
+            };
+            let size = [(6.0, 6.0), (4.0, 4.0), (4.0, 6.0)][i as usize / 2];
+            polygon(&TRIANGLE, size, quarters, white)
+        }
+        // lower right, lower left, upper left and upper right are the lower
+        // right one turned zero, three, two and one quarters
+        0x25E2..=0x25E5 => polygon(&CORNER, (6.0, 6.0), (4 - (c - 0x25E2)) % 4, false),
+        0x25F8..=0x25FA => polygon(&CORNER, (6.0, 6.0), [2, 1, 3][(c - 0x25F8) as usize], true),
+        0x25FF => polygon(&CORNER, (6.0, 6.0), 0, true),
+        0x25CB | 0x25CF => vec![circle(6, c == 0x25CB)],
+        0x25EF => vec![circle(8, true)],
+        0x25DC..=0x25DF => {
+            let start = [1, 0, 3, 2][(c - 0x25DC) as usize];
+            vec![circle_arc(start, start + 1)]
+        }
+        0x25E0 => vec![circle_arc(0, 2)],
+        0x25E1 => vec![circle_arc(2, 4)],
+        _ => unreachable!("0x{:X} is not a geometric shape with a recipe", c),
+    };
+    Recipe::new(c, format!("uni{:04X}", c), commands)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::drawing_command::Metrics;
+    use crate::font::Font;
+
+    #[test]
+    fn shapes_are_centred_in_the_block_area() {
+        let font = Font::new(Metrics::<f64>::default());
+
+        // black and white shapes have the same extent, mirrored shapes are
+        // mirror images, and white ones are hollow
+        let inked = |c: u32| {
+            let cell = font.render_cell(c, 10, 20, 1);
+            let (mut xs, mut ys) = (Vec::new(), Vec::new());
+            for (x, y) in (0..10).flat_map(|x| (0..20).map(move |y| (x, y))) {
+                if cell.get(x, y) != 0 {
+                    xs.push(x);
+                    ys.push(y);
+                }
+            }
+            let extent = |v: &[usize]| (*v.iter().min().unwrap(), *v.iter().max().unwrap());
+            (extent(&xs), extent(&ys), cell)
+        };
+        for (black, white) in [(0x25A0, 0x25A1), (0x25B2, 0x25B3), (0x25CF, 0x25CB)] {
+            let (black, white) = (inked(black), inked(white));
+            assert_eq!((black.0, black.1), (white.0, white.1));
+            assert_eq!(white.2.get(5, 11), 0);
+            assert_eq!(black.2.get(5, 11), 255);
+            assert_eq!(black.0 .0 + black.0 .1, 9);
+        }
+        for (right, left) in [(0x25B6, 0x25C0), (0x25BA, 0x25C4), (0x25E2, 0x25E3)] {
+            let (right, left) = (inked(right).2, inked(left).2);
+            for (x, y) in (0..10).flat_map(|x| (0..20).map(move |y| (x, y))) {
+                assert_eq!(right.get(x, y), left.get(9 - x, y));
+            }
+        }
+    }
+}
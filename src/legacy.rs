@@ -0,0 +1,346 @@
+//! Recipes for the Symbols for Legacy Computing, drawn here rather than
+//! generated from the original script.
+
+use std::ops::{AddAssign, SubAssign};
+
+use num::Float;
+
+use crate::commands::{Box_Builder, PolygonBuilder};
+use crate::drawing_command::{Metrics, Point};
+use crate::font::{Configure, Recipe};
+
+/// Sextant patterns with a character elsewhere: the left and right halves.
+const SEXTANT_HALVES: [u32; 2] = [0b010101, 0b101010];
+
+/// Octant patterns other than the quadrants with a character elsewhere: the
+/// upper and lower quarters and three quarters, the middle quarters and the
+/// corner eighths.
+const OCTANTS_ELSEWHERE: [u32; 10] = [
+    0b00000011, 0b11000000, 0b00111111, 0b11111100, 0b00010100, 0b00101000, 0b00000001, 0b00000010,
+    0b01000000, 0b10000000,
+];
+
+/// U+1FB3C to U+1FB6F, the smooth mosaic wedges, as counter-clockwise
+/// polygons on the sextant grid: halves of the width across, sixths of the
+/// block height up. Thirds are the sextant rows, the middle is only used by
+/// the triangular blocks.
+#[rustfmt::skip]
+const WEDGES: [&[(u32, u32)]; 52] = [
+    &[(0, 0), (1, 0), (0, 2)], // 1FB3C
+    &[(0, 0), (2, 0), (0, 2)], // 1FB3D
+    &[(0, 0), (1, 0), (0, 4)], // 1FB3E
+    &[(0, 0), (2, 0), (0, 4)], // 1FB3F
+    &[(0, 0), (1, 0), (0, 6)], // 1FB40
+    &[(0, 0), (2, 0), (2, 6), (1, 6), (0, 4)], // 1FB41
+    &[(0, 0), (2, 0), (2, 6), (0, 4)], // 1FB42
+    &[(0, 0), (2, 0), (2, 6), (1, 6), (0, 2)], // 1FB43
+    &[(0, 0), (2, 0), (2, 6), (0, 2)], // 1FB44
+    &[(0, 0), (2, 0), (2, 6), (1, 6)], // 1FB45
+    &[(0, 0), (2, 0), (2, 4), (0, 2)], // 1FB46
+    &[(1, 0), (2, 0), (2, 2)], // 1FB47
+    &[(0, 0), (2, 0), (2, 2)], // 1FB48
+    &[(1, 0), (2, 0), (2, 4)], // 1FB49
+    &[(0, 0), (2, 0), (2, 4)], // 1FB4A
+    &[(1, 0), (2, 0), (2, 6)], // 1FB4B
+    &[(0, 0), (2, 0), (2, 4), (1, 6), (0, 6)], // 1FB4C
+    &[(0, 0), (2, 0), (2, 4), (0, 6)], // 1FB4D
+    &[(0, 0), (2, 0), (2, 2), (1, 6), (0, 6)], // 1FB4E
+    &[(0, 0), (2, 0), (2, 2), (0, 6)], // 1FB4F
+    &[(0, 0), (2, 0), (1, 6), (0, 6)], // 1FB50
+    &[(0, 0), (2, 0), (2, 2), (0, 4)], // 1FB51
+    &[(1, 0), (2, 0), (2, 6), (0, 6), (0, 2)], // 1FB52
+    &[(2, 0), (2, 6), (0, 6), (0, 2)], // 1FB53
+    &[(1, 0), (2, 0), (2, 6), (0, 6), (0, 4)], // 1FB54
+    &[(2, 0), (2, 6), (0, 6), (0, 4)], // 1FB55
+    &[(1, 0), (2, 0), (2, 6), (0, 6)], // 1FB56
+    &[(0, 4), (1, 6), (0, 6)], // 1FB57
+    &[(0, 4), (2, 6), (0, 6)], // 1FB58
+    &[(0, 2), (1, 6), (0, 6)], // 1FB59
+    &[(0, 2), (2, 6), (0, 6)], // 1FB5A
+    &[(0, 0), (1, 6), (0, 6)], // 1FB5B
+    &[(0, 2), (2, 4), (2, 6), (0, 6)], // 1FB5C
+    &[(0, 0), (1, 0), (2, 2), (2, 6), (0, 6)], // 1FB5D
+    &[(0, 0), (2, 2), (2, 6), (0, 6)], // 1FB5E
+    &[(0, 0), (1, 0), (2, 4), (2, 6), (0, 6)], // 1FB5F
+    &[(0, 0), (2, 4), (2, 6), (0, 6)], // 1FB60
+    &[(0, 0), (1, 0), (2, 6), (0, 6)], // 1FB61
+    &[(2, 4), (2, 6), (1, 6)], // 1FB62
+    &[(2, 4), (2, 6), (0, 6)], // 1FB63
+    &[(2, 2), (2, 6), (1, 6)], // 1FB64
+    &[(2, 2), (2, 6), (0, 6)], // 1FB65
+    &[(2, 0), (2, 6), (1, 6)], // 1FB66
+    &[(2, 2), (2, 6), (0, 6), (0, 4)], // 1FB67
+    &[(0, 0), (2, 0), (2, 6), (0, 6), (1, 3)], // 1FB68
+    &[(0, 0), (2, 0), (2, 6), (1, 3), (0, 6)], // 1FB69
+    &[(0, 0), (2, 0), (1, 3), (2, 6), (0, 6)], // 1FB6A
+    &[(0, 0), (1, 3), (2, 0), (2, 6), (0, 6)], // 1FB6B
+    &[(0, 0), (1, 3), (0, 6)], // 1FB6C
+    &[(1, 3), (2, 6), (0, 6)], // 1FB6D
+    &[(2, 0), (2, 6), (1, 3)], // 1FB6E
+    &[(0, 0), (2, 0), (1, 3)], // 1FB6F
+];
+
+/// U+1FB70 to U+1FB8B, the eighth blocks and lines, as boxes on a grid of
+/// eighths of the width and block height: left, bottom, right and top.
+#[rustfmt::skip]
+const EIGHTHS: [&[(u32, u32, u32, u32)]; 28] = [
+    &[(1, 0, 2, 8)], // 1FB70
+    &[(2, 0, 3, 8)], // 1FB71
+    &[(3, 0, 4, 8)], // 1FB72
+    &[(4, 0, 5, 8)], // 1FB73
+    &[(5, 0, 6, 8)], // 1FB74
+    &[(6, 0, 7, 8)], // 1FB75
+    &[(0, 6, 8, 7)], // 1FB76
+    &[(0, 5, 8, 6)], // 1FB77
+    &[(0, 4, 8, 5)], // 1FB78
+    &[(0, 3, 8, 4)], // 1FB79
+    &[(0, 2, 8, 3)], // 1FB7A
+    &[(0, 1, 8, 2)], // 1FB7B
+    &[(0, 0, 1, 8), (1, 0, 8, 1)], // 1FB7C
+    &[(0, 0, 1, 8), (1, 7, 8, 8)], // 1FB7D
+    &[(7, 0, 8, 8), (0, 7, 7, 8)], // 1FB7E
+    &[(7, 0, 8, 8), (0, 0, 7, 1)], // 1FB7F
+    &[(0, 7, 8, 8), (0, 0, 8, 1)], // 1FB80
+    &[(0, 7, 8, 8), (0, 5, 8, 6), (0, 3, 8, 4), (0, 0, 8, 1)], // 1FB81
+    &[(0, 6, 8, 8)], // 1FB82
+    &[(0, 5, 8, 8)], // 1FB83
+    &[(0, 3, 8, 8)], // 1FB84
+    &[(0, 2, 8, 8)], // 1FB85
+    &[(0, 1, 8, 8)], // 1FB86
+    &[(6, 0, 8, 8)], // 1FB87
+    &[(5, 0, 8, 8)], // 1FB88
+    &[(3, 0, 8, 8)], // 1FB89
+    &[(2, 0, 8, 8)], // 1FB8A
+    &[(1, 0, 8, 8)], // 1FB8B
+];
+
+/// The point `x` of `columns` across and `y` of `rows` up the block area.
+///
+/// Inner lines fall at the same fractions of the width and block height as
+/// those of the quadrants, outer ones are those of the block area.
+fn grid_point<F: Float>(m: &Metrics<F>, x: u32, columns: u32, y: u32, rows: u32) -> Point<F> {
+    let f = |v: u32| F::from(v).unwrap();
+    let x = match x {
+        0 => m.block_origin.x,
+        x if x == columns => m.block_top.x,
+        x => m.width * f(x) / f(columns),
+    };
+    let y = match y {
+        0 => m.block_origin.y,
+        y if y == rows => m.block_top.y,
+        y => m.block_origin.y + m.block_height * f(y) / f(rows),
+    };
+    Point::new(x, y)
+}
+
+/// Boxes covering the cells set in `pattern`, on a grid of two columns and
+/// `rows` rows over the block area.
+///
+/// Bit `2 * row + column` stands for a cell, rows counted from the top.
+/// Neighbouring cells are merged into larger boxes.
+pub(crate) fn cells<F>(pattern: u32, rows: u32) -> Vec<Configure<F>>
+where
+    F: Float + AddAssign + SubAssign + 'static,
+{
+    let mut commands: Vec<Configure<F>> = Vec::new();
+    let mut row = 0;
+    while row < rows {
+        let columns = pattern >> (2 * row) & 0b11;
+        // rows below with the same columns set go in the same box
+        let mut end = row + 1;
+        while end < rows && pattern >> (2 * end) & 0b11 == columns {
+            end += 1;
+        }
+        if columns != 0 {
+            let (left, right) = match columns {
+                0b01 => (0, 1),
+                0b10 => (1, 2),
+                _ => (0, 2),
+            };
+            let (bottom, top) = (rows - end, rows - row);
+            commands.push(Box::new(move |m: &Metrics<F>| {
+                let mut builder = Box_Builder::default();
+                builder.start(grid_point(m, left, 2, bottom, rows));
+                builder.end(grid_point(m, right, 2, top, rows));
+                builder.build().unwrap().into()
+            }));
+        }
+        row = end;
+    }
+    commands
+}
+
+/// The cells of a pattern as in character names, e.g. `135`.
+pub(crate) fn cell_numbers(pattern: u32) -> String {
+    (0..u32::BITS)
+        .filter(|bit| pattern >> bit & 1 == 1)
+        .map(|bit| char::from_digit(bit + 1, 10).unwrap())
+        .collect()
+}
+
+/// U+1FB00 to U+1FB3B, the 2×3 sextants, in the order of their patterns.
+pub(crate) fn sextant<F>(c: u32) -> Recipe<F>
+where
+    F: Float + AddAssign + SubAssign + 'static,
+{
+    let pattern = (1..0b111111)
+        .filter(|pattern| !SEXTANT_HALVES.contains(pattern))
+        .nth((c - 0x1FB00) as usize)
+        .unwrap_or_else(|| unreachable!("0x{:X} is not a sextant", c));
+    Recipe::new(
+        c,
+        format!("sextant{}", cell_numbers(pattern)),
+        cells(pattern, 3),
+    )
+}
+
+/// U+1CD00 to U+1CDE5, the 2×4 octants, in the order of their patterns.
+///
+/// Patterns made of quadrants, where each pair of rows is the same, are
+/// left to the block elements.
+pub(crate) fn octant<F>(c: u32) -> Recipe<F>
+where
+    F: Float + AddAssign + SubAssign + 'static,
+{
+    let quadrants = |pattern: u32| pattern & 0b00110011 == pattern >> 2 & 0b00110011;
+    let pattern = (0..=0b11111111)
+        .filter(|pattern| !quadrants(*pattern) && !OCTANTS_ELSEWHERE.contains(pattern))
+        .nth((c - 0x1CD00) as usize)
+        .unwrap_or_else(|| unreachable!("0x{:X} is not an octant", c));
+    Recipe::new(
+        c,
+        format!("octant{}", cell_numbers(pattern)),
+        cells(pattern, 4),
+    )
+}
+
+/// U+1FB3C to U+1FB6F, the smooth mosaic wedges.
+pub(crate) fn wedge<F>(c: u32) -> Recipe<F>
+where
+    F: Float + AddAssign + SubAssign + 'static,
+{
+    let points = WEDGES[(c - 0x1FB3C) as usize];
+    let configure: Configure<F> = Box::new(move |m: &Metrics<F>| {
+        let mut builder = PolygonBuilder::default();
+        builder.points(
+            points
+                .iter()
+                .map(|(x, y)| grid_point(m, *x, 2, *y, 6))
+                .collect(),
+        );
+        builder.build().unwrap().into()
+    });
+    Recipe::new(c, format!("u{:X}", c), vec![configure])
+}
+
+/// U+1FB70 to U+1FB8B, the eighth blocks and lines, on the same fractions
+/// as the block elements from U+2581 to U+2590.
+pub(crate) fn eighth<F>(c: u32) -> Recipe<F>
+where
+    F: Float + AddAssign + SubAssign + 'static,
+{
+    let commands = EIGHTHS[(c - 0x1FB70) as usize]
+        .iter()
+        .map(|(left, bottom, right, top)| -> Configure<F> {
+            let (left, bottom, right, top) = (*left, *bottom, *right, *top);
+            Box::new(move |m: &Metrics<F>| {
+                let mut builder = Box_Builder::default();
+                builder.start(grid_point(m, left, 8, bottom, 8));
+                builder.end(grid_point(m, right, 8, top, 8));
+                builder.build().unwrap().into()
+            })
+        })
+        .collect();
+    Recipe::new(c, format!("u{:X}", c), commands)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::drawing_command::Metrics;
+    use crate::font::Font;
+
+    #[test]
+    fn sextants_tile_with_quadrants() {
+        let font = Font::new(Metrics::<f64>::default());
+        assert_eq!(Font::<f64>::glyph_name(0x1FB00), "sextant1");
+        assert_eq!(Font::<f64>::glyph_name(0x1FB3B), "sextant23456");
+
+        // sextant-1235 and sextant-46 make a full block, the top row of
+        // sextant-12 covers the top third of the upper half block
+        let left = font.render_cell(0x1FB15, 12, 24, 2);
+        let right = font.render_cell(0x1FB26, 12, 24, 2);
+        for (l, r) in left.data.iter().zip(right.data.iter()) {
+            assert_eq!(l.max(r), &255);
+            assert_eq!(l.min(r), &0);
+        }
+        let top = font.render_cell(0x1FB02, 12, 24, 2);
+        let half = font.render_cell(0x2580, 12, 24, 2);
+        assert_eq!(top.data[..8 * 12], half.data[..8 * 12]);
+    }
+
+    #[test]
+    fn octants_tile_with_quadrants() {
+        let font = Font::new(Metrics::<f64>::default());
+        assert_eq!(Font::<f64>::glyph_name(0x1CD00), "octant3");
+        assert_eq!(Font::<f64>::glyph_name(0x1CDE5), "octant2345678");
+
+        // octant-3 is the lower half of the upper left quadrant, and
+        // octant-1245678 the rest of the full block
+        let octant = font.render_cell(0x1CD00, 12, 24, 2);
+        let quadrant = font.render_cell(0x2598, 12, 24, 2);
+        let rest = font.render_cell(0x1CDE3, 12, 24, 2);
+        assert_eq!(Font::<f64>::glyph_name(0x1CDE3), "octant1245678");
+        for y in 0..24 {
+            for x in 0..12 {
+                let expected = if y < 6 { 0 } else { quadrant.get(x, y) };
+                assert_eq!(octant.get(x, y), expected);
+                assert_eq!(octant.get(x, y).max(rest.get(x, y)), 255);
+            }
+        }
+    }
+
+    #[test]
+    fn wedges_complement_each_other() {
+        let font = Font::new(Metrics::<f64>::default());
+        // lower and upper wedges along the same diagonal, and the triangular
+        // three quarters and one quarter blocks
+        let pairs = (0x1FB3C..=0x1FB51)
+            .map(|c| (c, c + 0x16))
+            .chain((0x1FB68..=0x1FB6B).map(|c| (c, c + 4)));
+        for (a, b) in pairs {
+            let a = font.render_cell(a, 12, 24, 2);
+            let b = font.render_cell(b, 12, 24, 2);
+            for (a, b) in a.data.iter().zip(b.data.iter()) {
+                assert!((*a as i32 + *b as i32 - 255).abs() <= 1);
+            }
+        }
+    }
+
+    #[test]
+    fn eighths_tile_the_full_block() {
+        let font = Font::new(Metrics::<f64>::default());
+        let full = font.render_cell(0x2588, 16, 24, 2);
+        // left to right, and top to bottom, with the block elements at
+        // either end
+        let columns = [0x258F]
+            .into_iter()
+            .chain(0x1FB70..=0x1FB75)
+            .chain([0x2595]);
+        let rows = [0x2594]
+            .into_iter()
+            .chain(0x1FB76..=0x1FB7B)
+            .chain([0x2581]);
+        for eighths in [columns.collect::<Vec<_>>(), rows.collect()] {
+            let mut sum = vec![0; full.data.len()];
+            for c in eighths {
+                let cell = font.render_cell(c, 16, 24, 2);
+                for (s, v) in sum.iter_mut().zip(cell.data.iter()) {
+                    *s += *v as u32;
+                }
+            }
+            for (s, v) in sum.iter().zip(full.data.iter()) {
+                assert_eq!(*s, *v as u32);
+            }
+        }
+    }
+}
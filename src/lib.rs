@@ -1,11 +1,39 @@
+mod atlas;
+mod bdf;
+mod braille;
+mod branch;
 mod commands;
+mod connection;
 mod drawing_command;
 mod font;
+mod geometric;
+mod legacy;
+mod outline;
+mod overlap;
+mod powerline;
+mod progress;
+mod psf;
+mod quadratic;
+mod raster;
+mod sfnt;
+mod svg;
+mod technical;
+mod ufo;
 
 pub mod prelude {
+    pub use crate::atlas::{Atlas, AtlasBuilder, AtlasGlyph};
+    pub use crate::bdf::BdfWriter;
     pub use crate::commands::Commands;
-    pub use crate::drawing_command::{Canvas, DrawingCommand, Metrics};
-    pub use crate::font::Font;
+    pub use crate::connection::{char_for, connection_of, Arm, Connection};
+    pub use crate::drawing_command::{Canvas, DotShape, DrawingCommand, Metrics, Point};
+    pub use crate::font::{CodePointError, Font, PrivateUse};
+    pub use crate::outline::{Contour, Outline, OutlineBuilder, PathSegment, Rect};
+    pub use crate::psf::PsfWriter;
+    pub use crate::quadratic::{fonts_to_quadratic, IncompatibleOutlines};
+    pub use crate::raster::{Bitmap, Rasterizer};
+    pub use crate::sfnt::{FontFile, FontPatcher, SfntError, TrueTypeWriter};
+    pub use crate::svg::SvgCanvas;
+    pub use crate::ufo::{MergePolicy, MergeReport, Ufo, UfoWriter};
 }
 
 #[cfg(test)]
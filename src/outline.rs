@@ -0,0 +1,564 @@
+use std::cell::RefCell;
+
+use num::Float;
+
+use crate::drawing_command::{two, Canvas, Point};
+use crate::overlap;
+
+/// A single drawing instruction of a contour.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PathSegment<F: Float> {
+    Move(Point<F>),
+    Line(Point<F>),
+    /// Cubic bezier curve: two control points and the end point.
+    Cubic(Point<F>, Point<F>, Point<F>),
+    /// Quadratic bezier curve: the control point and the end point.
+    Quad(Point<F>, Point<F>),
+    Close,
+}
+
+impl<F: Float> PathSegment<F> {
+    /// The point the segment ends on, if any.
+    pub fn end(&self) -> Option<Point<F>> {
+        match self {
+            PathSegment::Move(pt)
+            | PathSegment::Line(pt)
+            | PathSegment::Cubic(_, _, pt)
+            | PathSegment::Quad(_, pt) => Some(*pt),
+            PathSegment::Close => None,
+        }
+    }
+
+    /// All points of the segment, including control points.
+    pub fn points(&self) -> impl Iterator<Item = &Point<F>> {
+        let points = match self {
+            PathSegment::Move(pt) | PathSegment::Line(pt) => [Some(pt), None, None],
+            PathSegment::Cubic(pt1, pt2, end) => [Some(pt1), Some(pt2), Some(end)],
+            PathSegment::Quad(pt1, end) => [Some(pt1), Some(end), None],
+            PathSegment::Close => [None, None, None],
+        };
+        points.into_iter().flatten()
+    }
+
+    /// The segment as a cubic curve starting at `current`. Quadratic curves are
+    /// elevated, lines and moves give `None`.
+    pub fn as_cubic(&self, current: &Point<F>) -> Option<(Point<F>, Point<F>, Point<F>)> {
+        match *self {
+            PathSegment::Cubic(pt1, pt2, end) => Some((pt1, pt2, end)),
+            PathSegment::Quad(pt1, end) => {
+                let two_thirds = two::<F>() / (two::<F>() + F::one());
+                Some((
+                    Point::new(
+                        current.x + (pt1.x - current.x) * two_thirds,
+                        current.y + (pt1.y - current.y) * two_thirds,
+                    ),
+                    Point::new(
+                        end.x + (pt1.x - end.x) * two_thirds,
+                        end.y + (pt1.y - end.y) * two_thirds,
+                    ),
+                    end,
+                ))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// An axis-aligned rectangle.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rect<F: Float> {
+    pub min: Point<F>,
+    pub max: Point<F>,
+}
+
+impl<F: Float> Rect<F> {
+    fn from_point(pt: Point<F>) -> Rect<F> {
+        Rect { min: pt, max: pt }
+    }
+
+    fn include(&mut self, pt: Point<F>) {
+        self.min = Point::new(self.min.x.min(pt.x), self.min.y.min(pt.y));
+        self.max = Point::new(self.max.x.max(pt.x), self.max.y.max(pt.y));
+    }
+
+    fn union(self, other: Rect<F>) -> Rect<F> {
+        let mut rect = self;
+        rect.include(other.min);
+        rect.include(other.max);
+        rect
+    }
+
+    /// Whether `other` lies within this rectangle.
+    pub fn contains(&self, other: &Rect<F>) -> bool {
+        self.min.x <= other.min.x
+            && self.min.y <= other.min.y
+            && self.max.x >= other.max.x
+            && self.max.y >= other.max.y
+    }
+
+    pub fn width(&self) -> F {
+        self.max.x - self.min.x
+    }
+
+    pub fn height(&self) -> F {
+        self.max.y - self.min.y
+    }
+}
+
+/// A closed or open sequence of segments, always starting with a move.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Contour<F: Float> {
+    segments: Vec<PathSegment<F>>,
+}
+
+impl<F: Float> Contour<F> {
+    pub fn segments(&self) -> &[PathSegment<F>] {
+        &self.segments
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, PathSegment<F>> {
+        self.segments.iter()
+    }
+
+    pub fn is_closed(&self) -> bool {
+        matches!(self.segments.last(), Some(PathSegment::Close))
+    }
+
+    /// The on-curve point the contour starts from.
+    pub fn start(&self) -> Point<F> {
+        self.segments[0].end().unwrap()
+    }
+
+    pub fn points(&self) -> impl Iterator<Item = &Point<F>> {
+        self.segments.iter().flat_map(|segment| segment.points())
+    }
+
+    /// Bounding box of all points, including off-curve control points.
+    pub fn control_box(&self) -> Rect<F> {
+        let mut points = self.points();
+        let mut rect = Rect::from_point(*points.next().unwrap());
+        points.for_each(|pt| rect.include(*pt));
+        rect
+    }
+
+    /// Tight bounding box of the drawn shape.
+    pub fn bounds(&self) -> Rect<F> {
+        let mut rect = Rect::from_point(self.start());
+        let mut current = self.start();
+        for segment in self.segments.iter() {
+            if let Some((pt1, pt2, end)) = segment.as_cubic(&current) {
+                for t in cubic_extrema(current.x, pt1.x, pt2.x, end.x)
+                    .chain(cubic_extrema(current.y, pt1.y, pt2.y, end.y))
+                {
+                    rect.include(cubic_at(&current, &pt1, &pt2, &end, t));
+                }
+            }
+            if let Some(end) = segment.end() {
+                rect.include(end);
+                current = end;
+            }
+        }
+        rect
+    }
+
+    /// The same contour, drawn in the opposite direction.
+    ///
+    /// A closed contour keeps its start point.
+    pub fn reversed(&self) -> Contour<F> {
+        let closed = self.is_closed();
+        let mut points = vec![self.start()];
+        let mut drawn = Vec::new();
+        for segment in self.segments.iter().skip(1) {
+            if let Some(end) = segment.end() {
+                points.push(end);
+                drawn.push(segment);
+            }
+        }
+        let last = *points.last().unwrap();
+
+        let mut segments = Vec::with_capacity(self.segments.len() + 1);
+        if closed {
+            segments.push(PathSegment::Move(points[0]));
+            if last != points[0] {
+                segments.push(PathSegment::Line(last));
+            }
+        } else {
+            segments.push(PathSegment::Move(last));
+        }
+        for (segment, target) in drawn.iter().zip(points.iter()).rev() {
+            segments.push(match segment {
+                PathSegment::Cubic(pt1, pt2, _) => PathSegment::Cubic(*pt2, *pt1, *target),
+                PathSegment::Quad(pt1, _) => PathSegment::Quad(*pt1, *target),
+                _ => PathSegment::Line(*target),
+            });
+        }
+        if closed {
+            if segments.last() == Some(&PathSegment::Line(points[0])) {
+                segments.pop();
+            }
+            segments.push(PathSegment::Close);
+        }
+        Contour { segments }
+    }
+
+    /// The contour as a polygon, with curves approximated by a few lines.
+    fn polygon(&self) -> Vec<(f64, f64)> {
+        let xy = |pt: &Point<F>| (pt.x.to_f64().unwrap(), pt.y.to_f64().unwrap());
+        let mut polygon = vec![xy(&self.start())];
+        let mut current = self.start();
+        for segment in self.segments.iter().skip(1) {
+            if let Some((pt1, pt2, end)) = segment.as_cubic(&current) {
+                for i in 1..8 {
+                    let t = F::from(i as f64 / 8.).unwrap();
+                    polygon.push(xy(&cubic_at(&current, &pt1, &pt2, &end, t)));
+                }
+            }
+            if let Some(end) = segment.end() {
+                polygon.push(xy(&end));
+                current = end;
+            }
+        }
+        polygon
+    }
+
+    /// Signed area enclosed by the contour; positive when it runs
+    /// counter-clockwise.
+    pub fn area(&self) -> F {
+        let polygon = self.polygon();
+        let area: f64 = polygon
+            .iter()
+            .zip(polygon.iter().cycle().skip(1))
+            .map(|(a, b)| (a.0 * b.1 - b.0 * a.1) / 2.)
+            .sum();
+        F::from(area).unwrap()
+    }
+}
+
+impl<'a, F: Float> IntoIterator for &'a Contour<F> {
+    type Item = &'a PathSegment<F>;
+    type IntoIter = std::slice::Iter<'a, PathSegment<F>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.segments.iter()
+    }
+}
+
+/// The drawing of a glyph, collected into contours.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Outline<F: Float> {
+    contours: Vec<Contour<F>>,
+}
+
+impl<F: Float> Default for Outline<F> {
+    fn default() -> Self {
+        Outline {
+            contours: Vec::new(),
+        }
+    }
+}
+
+impl<F: Float> Outline<F> {
+    pub fn new() -> Outline<F> {
+        Outline::default()
+    }
+
+    pub fn contours(&self) -> &[Contour<F>] {
+        &self.contours
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.contours.is_empty()
+    }
+
+    /// All segments of all contours, in drawing order.
+    pub fn segments(&self) -> impl Iterator<Item = &PathSegment<F>> {
+        self.contours.iter().flat_map(|contour| contour.iter())
+    }
+
+    pub fn points(&self) -> impl Iterator<Item = &Point<F>> {
+        self.contours.iter().flat_map(|contour| contour.points())
+    }
+
+    /// Bounding box of all points, including off-curve control points.
+    /// `None` for an empty outline.
+    pub fn control_box(&self) -> Option<Rect<F>> {
+        self.contours
+            .iter()
+            .map(Contour::control_box)
+            .reduce(Rect::union)
+    }
+
+    /// Tight bounding box of the drawn shape. `None` for an empty outline.
+    pub fn bounds(&self) -> Option<Rect<F>> {
+        self.contours
+            .iter()
+            .map(Contour::bounds)
+            .reduce(Rect::union)
+    }
+
+    /// Merges overlapping contours, like `removeOverlap()` in font editors.
+    ///
+    /// The result covers the same area, filled with the non-zero winding
+    /// rule, with contours that neither overlap nor cross each other. Outer
+    /// contours run counter-clockwise and holes clockwise.
+    pub fn remove_overlap(&self) -> Outline<F> {
+        overlap::remove_overlap(self)
+    }
+
+    /// The same outline with every contour drawn in the opposite direction.
+    pub fn reversed(&self) -> Outline<F> {
+        Outline {
+            contours: self.contours.iter().map(Contour::reversed).collect(),
+        }
+    }
+
+    /// Sets the direction of all contours, like `correctDirection()` in font
+    /// editors.
+    ///
+    /// Outer contours run counter-clockwise and the contours of holes
+    /// clockwise, as expected by PostScript outlines. With `true_type` set
+    /// it is the other way round, as expected by TrueType outlines.
+    pub fn correct_direction(&self, true_type: bool) -> Outline<F> {
+        self.flip_contours(&self.direction_flips(true_type))
+    }
+
+    /// Which contours `correct_direction` reverses.
+    ///
+    /// A contour is a hole when it lies within an odd number of other
+    /// contours. Contours that merely overlap do not nest.
+    pub(crate) fn direction_flips(&self, true_type: bool) -> Vec<bool> {
+        let polygons: Vec<_> = self.contours.iter().map(Contour::polygon).collect();
+        let boxes: Vec<_> = self.contours.iter().map(Contour::control_box).collect();
+        let areas: Vec<_> = self.contours.iter().map(Contour::area).collect();
+        (0..self.contours.len())
+            .map(|i| {
+                let counter_clockwise = areas[i] > F::zero();
+                // a point just inside the contour, next to its first edge
+                let polygon = &polygons[i];
+                let (a, b) = (polygon[0], polygon[1 % polygon.len()]);
+                let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+                let side = if counter_clockwise { 1e-6 } else { -1e-6 };
+                let inside = ((a.0 + b.0) / 2. - dy * side, (a.1 + b.1) / 2. + dx * side);
+
+                let depth = (0..polygons.len())
+                    .filter(|j| {
+                        *j != i
+                            && areas[*j].abs() > areas[i].abs()
+                            && boxes[*j].contains(&boxes[i])
+                            && encloses(&polygons[*j], inside)
+                    })
+                    .count();
+                let outer = depth % 2 == 0;
+                counter_clockwise != (outer != true_type)
+            })
+            .collect()
+    }
+
+    pub(crate) fn flip_contours(&self, flips: &[bool]) -> Outline<F> {
+        let contours = self
+            .contours
+            .iter()
+            .zip(flips)
+            .map(|(contour, flip)| {
+                if *flip {
+                    contour.reversed()
+                } else {
+                    contour.clone()
+                }
+            })
+            .collect();
+        Outline { contours }
+    }
+
+    /// Draws the outline again, onto another canvas.
+    ///
+    /// Quadratic curves are drawn as the equivalent cubic curves.
+    pub fn replay<C: Canvas<F>>(&self, canvas: &mut C) {
+        for contour in self.contours.iter() {
+            let mut current = contour.start();
+            for segment in contour.iter() {
+                match segment {
+                    PathSegment::Move(pt) => canvas.move_to(pt),
+                    PathSegment::Line(pt) => canvas.line_to(pt),
+                    PathSegment::Cubic(pt1, pt2, end) => canvas.curve_to(pt1, pt2, end),
+                    PathSegment::Quad(..) => {
+                        let (pt1, pt2, end) = segment.as_cubic(&current).unwrap();
+                        canvas.curve_to(&pt1, &pt2, &end)
+                    }
+                    PathSegment::Close => canvas.close_path(),
+                }
+                current = segment.end().unwrap_or(current);
+            }
+        }
+    }
+
+    pub(crate) fn push(&mut self, segment: PathSegment<F>) {
+        match (segment, self.contours.last_mut()) {
+            (PathSegment::Close, None) => {}
+            (PathSegment::Close, Some(contour)) if contour.is_closed() => {}
+            (PathSegment::Move(_), _) | (_, None) => self.contours.push(Contour {
+                segments: vec![segment],
+            }),
+            (_, Some(contour)) if contour.is_closed() => {
+                // drawing on after closing a path continues from its start
+                let start = contour.start();
+                self.contours.push(Contour {
+                    segments: vec![PathSegment::Move(start), segment],
+                });
+            }
+            (_, Some(contour)) => contour.segments.push(segment),
+        }
+    }
+}
+
+impl<'a, F: Float> IntoIterator for &'a Outline<F> {
+    type Item = &'a Contour<F>;
+    type IntoIter = std::slice::Iter<'a, Contour<F>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.contours.iter()
+    }
+}
+
+/// A canvas that records the drawing into an [`Outline`].
+pub struct OutlineBuilder<F: Float> {
+    outline: RefCell<Outline<F>>,
+}
+
+impl<F: Float> Default for OutlineBuilder<F> {
+    fn default() -> Self {
+        OutlineBuilder {
+            outline: RefCell::new(Outline::new()),
+        }
+    }
+}
+
+impl<F: Float> OutlineBuilder<F> {
+    pub fn new() -> OutlineBuilder<F> {
+        OutlineBuilder::default()
+    }
+
+    pub fn into_outline(self) -> Outline<F> {
+        self.outline.into_inner()
+    }
+}
+
+impl<F: Float> Canvas<F> for OutlineBuilder<F> {
+    fn move_to(&self, pt: &Point<F>) {
+        self.outline.borrow_mut().push(PathSegment::Move(*pt));
+    }
+
+    fn line_to(&self, pt: &Point<F>) {
+        self.outline.borrow_mut().push(PathSegment::Line(*pt));
+    }
+
+    fn curve_to(&self, pt1: &Point<F>, pt2: &Point<F>, ptend: &Point<F>) {
+        self.outline
+            .borrow_mut()
+            .push(PathSegment::Cubic(*pt1, *pt2, *ptend));
+    }
+
+    fn close_path(&self) {
+        self.outline.borrow_mut().push(PathSegment::Close);
+    }
+}
+
+/// Even-odd test whether a point is inside a polygon.
+fn encloses(polygon: &[(f64, f64)], (x, y): (f64, f64)) -> bool {
+    let mut inside = false;
+    for (a, b) in polygon.iter().zip(polygon.iter().cycle().skip(1)) {
+        if (a.1 > y) != (b.1 > y) && x < a.0 + (y - a.1) * (b.0 - a.0) / (b.1 - a.1) {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+/// Point on a cubic bezier curve at `t`.
+pub(crate) fn cubic_at<F: Float>(
+    p0: &Point<F>,
+    p1: &Point<F>,
+    p2: &Point<F>,
+    p3: &Point<F>,
+    t: F,
+) -> Point<F> {
+    let mt = F::one() - t;
+    let three = two::<F>() + F::one();
+    let (a, b, c, d) = (
+        mt * mt * mt,
+        three * mt * mt * t,
+        three * mt * t * t,
+        t * t * t,
+    );
+    Point::new(
+        a * p0.x + b * p1.x + c * p2.x + d * p3.x,
+        a * p0.y + b * p1.y + c * p2.y + d * p3.y,
+    )
+}
+
+/// Parameters in `(0, 1)` where a cubic bezier is extreme in one dimension.
+fn cubic_extrema<F: Float>(p0: F, p1: F, p2: F, p3: F) -> impl Iterator<Item = F> {
+    // derivative: a t^2 + b t + c
+    let three = two::<F>() + F::one();
+    let a = three * (p3 - p0 + three * (p1 - p2));
+    let b = two::<F>() * three * (p0 - two::<F>() * p1 + p2);
+    let c = three * (p1 - p0);
+
+    let roots = if a.abs() < F::epsilon() {
+        if b.abs() < F::epsilon() {
+            [None, None]
+        } else {
+            [Some(-c / b), None]
+        }
+    } else {
+        let discriminant = b * b - two::<F>() * two::<F>() * a * c;
+        if discriminant < F::zero() {
+            [None, None]
+        } else {
+            let sqrt = discriminant.sqrt();
+            [
+                Some((-b + sqrt) / (two::<F>() * a)),
+                Some((-b - sqrt) / (two::<F>() * a)),
+            ]
+        }
+    };
+    roots
+        .into_iter()
+        .flatten()
+        .filter(|t| *t > F::zero() && *t < F::one())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::drawing_command::Metrics;
+    use crate::font::Font;
+
+    #[test]
+    fn full_block() {
+        let font = Font::new(Metrics::<f64>::default());
+        let outline = font.outline(0x2588);
+        assert_eq!(outline.contours().len(), 1);
+        assert_eq!(
+            outline.contours()[0].segments(),
+            &[
+                PathSegment::Move(Point::new(0., -400.)),
+                PathSegment::Line(Point::new(600., -400.)),
+                PathSegment::Line(Point::new(600., 1000.)),
+                PathSegment::Line(Point::new(0., 1000.)),
+                PathSegment::Close,
+            ]
+        );
+    }
+
+    #[test]
+    fn arc_bounds_stay_inside_control_box() {
+        let font = Font::new(Metrics::<f64>::default());
+        let outline = font.outline(0x256D);
+        let bounds = outline.bounds().unwrap();
+        let control_box = outline.control_box().unwrap();
+        assert_eq!(bounds, control_box);
+        assert_eq!(bounds.min, Point::new(220., -400.));
+        assert_eq!(bounds.max, Point::new(680., 380.));
+    }
+}
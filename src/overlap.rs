@@ -0,0 +1,551 @@
+//! Boolean union of the contours of an outline.
+//!
+//! Curves are flattened into line pieces that remember which part of which
+//! curve they came from. All pieces are split where they touch or cross each
+//! other, and every piece that separates filled from unfilled area (under the
+//! non-zero winding rule) is kept. The kept pieces are chained into new
+//! contours, and runs of pieces from the same curve are turned back into a
+//! single cubic segment.
+
+use std::collections::HashMap;
+
+use num::Float;
+
+use crate::drawing_command::Point;
+use crate::outline::{Outline, PathSegment};
+
+/// Grid cells per font unit that all vertices are snapped to.
+const GRID: f64 = 1024.;
+/// Maximum distance, in font units, between a curve and its flattened pieces.
+const FLATNESS: f64 = 0.05;
+
+type Vertex = (i64, i64);
+type Cubic = [(f64, f64); 4];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Origin {
+    Line,
+    /// Part of curve `index`, from parameter `t0` at the start of the edge to
+    /// `t1` at its end.
+    Curve {
+        index: usize,
+        t0: f64,
+        t1: f64,
+    },
+}
+
+impl Origin {
+    fn reversed(self) -> Origin {
+        match self {
+            Origin::Line => Origin::Line,
+            Origin::Curve { index, t0, t1 } => Origin::Curve {
+                index,
+                t0: t1,
+                t1: t0,
+            },
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Edge {
+    from: Vertex,
+    to: Vertex,
+    origin: Origin,
+}
+
+impl Edge {
+    fn min_x(&self) -> i64 {
+        self.from.0.min(self.to.0)
+    }
+
+    fn max_x(&self) -> i64 {
+        self.from.0.max(self.to.0)
+    }
+
+    fn min_y(&self) -> i64 {
+        self.from.1.min(self.to.1)
+    }
+
+    fn max_y(&self) -> i64 {
+        self.from.1.max(self.to.1)
+    }
+
+    /// Whether `pt` lies on the edge, but is not one of its end points.
+    fn touches(&self, pt: Vertex) -> bool {
+        pt != self.from
+            && pt != self.to
+            && orient(self.from, self.to, pt) == 0
+            && (self.min_x()..=self.max_x()).contains(&pt.0)
+            && (self.min_y()..=self.max_y()).contains(&pt.1)
+    }
+
+    /// Position of `pt` along the edge, 0 at the start and 1 at the end.
+    fn param(&self, pt: Vertex) -> f64 {
+        let (dx, dy) = (
+            (self.to.0 - self.from.0) as f64,
+            (self.to.1 - self.from.1) as f64,
+        );
+        let (px, py) = ((pt.0 - self.from.0) as f64, (pt.1 - self.from.1) as f64);
+        (px * dx + py * dy) / (dx * dx + dy * dy)
+    }
+}
+
+/// Coincident edges, merged into one.
+struct Group {
+    from: Vertex,
+    to: Vertex,
+    /// Sum of the directions of all coincident edges, +1 for each edge
+    /// running from `from` to `to`.
+    winding: i32,
+    origin: Origin,
+}
+
+pub(crate) fn remove_overlap<F: Float>(outline: &Outline<F>) -> Outline<F> {
+    let (edges, curves) = flatten(outline);
+    let edges = split(&edges);
+    let kept = classify(&edges);
+    let mut result = Outline::new();
+    for contour in chain(&kept) {
+        if area(&contour).abs() >= 1. {
+            rebuild(&mut result, &contour, &curves);
+        }
+    }
+    result
+}
+
+fn snap(x: f64, y: f64) -> Vertex {
+    ((x * GRID).round() as i64, (y * GRID).round() as i64)
+}
+
+fn unsnap<F: Float>(v: Vertex) -> Point<F> {
+    Point::new(
+        F::from(v.0 as f64 / GRID).unwrap(),
+        F::from(v.1 as f64 / GRID).unwrap(),
+    )
+}
+
+/// Sign of the turn from `a` over `b` to `c`; positive counter-clockwise.
+fn orient(a: Vertex, b: Vertex, c: Vertex) -> i32 {
+    let cross =
+        (b.0 - a.0) as i128 * (c.1 - a.1) as i128 - (b.1 - a.1) as i128 * (c.0 - a.0) as i128;
+    cross.signum() as i32
+}
+
+fn cubic_at(c: &Cubic, t: f64) -> (f64, f64) {
+    let mt = 1. - t;
+    let (a, b, d, e) = (mt * mt * mt, 3. * mt * mt * t, 3. * mt * t * t, t * t * t);
+    (
+        a * c[0].0 + b * c[1].0 + d * c[2].0 + e * c[3].0,
+        a * c[0].1 + b * c[1].1 + d * c[2].1 + e * c[3].1,
+    )
+}
+
+/// Turns the outline into snapped line pieces. Open contours are closed.
+fn flatten<F: Float>(outline: &Outline<F>) -> (Vec<Edge>, Vec<Cubic>) {
+    let xy = |pt: &Point<F>| (pt.x.to_f64().unwrap(), pt.y.to_f64().unwrap());
+    let mut edges = Vec::new();
+    let mut curves = Vec::new();
+    let mut push = |from: Vertex, to: Vertex, origin: Origin| {
+        if from != to {
+            edges.push(Edge { from, to, origin });
+        }
+    };
+
+    for contour in outline.contours() {
+        let start = xy(&contour.start());
+        let mut current = start;
+        let mut current_point = contour.start();
+        for segment in contour.segments() {
+            match segment {
+                PathSegment::Move(_) | PathSegment::Close => {}
+                PathSegment::Line(pt) => {
+                    let pt = xy(pt);
+                    push(snap(current.0, current.1), snap(pt.0, pt.1), Origin::Line);
+                    current = pt;
+                }
+                PathSegment::Cubic(..) | PathSegment::Quad(..) => {
+                    let (pt1, pt2, end) = segment.as_cubic(&current_point).unwrap();
+                    let curve = [current, xy(&pt1), xy(&pt2), xy(&end)];
+                    let dd = |a: (f64, f64), b: (f64, f64), c: (f64, f64)| {
+                        (a.0 - 2. * b.0 + c.0).hypot(a.1 - 2. * b.1 + c.1)
+                    };
+                    let d = dd(curve[0], curve[1], curve[2]).max(dd(curve[1], curve[2], curve[3]));
+                    let n = ((0.75 * d / FLATNESS).sqrt().ceil() as usize).clamp(1, 64);
+
+                    let index = curves.len();
+                    let mut from = snap(current.0, current.1);
+                    let mut t0 = 0.;
+                    for i in 1..=n {
+                        let t1 = i as f64 / n as f64;
+                        let pt = cubic_at(&curve, t1);
+                        let to = snap(pt.0, pt.1);
+                        if to != from {
+                            push(from, to, Origin::Curve { index, t0, t1 });
+                            from = to;
+                            t0 = t1;
+                        }
+                    }
+                    curves.push(curve);
+                    current = curve[3];
+                }
+            }
+            current_point = segment.end().unwrap_or(current_point);
+        }
+        push(
+            snap(current.0, current.1),
+            snap(start.0, start.1),
+            Origin::Line,
+        );
+    }
+    (edges, curves)
+}
+
+/// Splits all edges wherever another edge crosses them or ends on them.
+fn split(edges: &[Edge]) -> Vec<Edge> {
+    let mut splits: Vec<Vec<Vertex>> = vec![Vec::new(); edges.len()];
+
+    let mut order: Vec<usize> = (0..edges.len()).collect();
+    order.sort_by_key(|i| edges[*i].min_x());
+    for (n, &i) in order.iter().enumerate() {
+        let a = &edges[i];
+        for &j in order[n + 1..].iter() {
+            let b = &edges[j];
+            if b.min_x() > a.max_x() {
+                break;
+            }
+            if b.min_y() > a.max_y() || a.min_y() > b.max_y() {
+                continue;
+            }
+
+            for pt in [b.from, b.to] {
+                if a.touches(pt) {
+                    splits[i].push(pt);
+                }
+            }
+            for pt in [a.from, a.to] {
+                if b.touches(pt) {
+                    splits[j].push(pt);
+                }
+            }
+
+            let (o1, o2) = (orient(a.from, a.to, b.from), orient(a.from, a.to, b.to));
+            let (o3, o4) = (orient(b.from, b.to, a.from), orient(b.from, b.to, a.to));
+            if o1 * o2 < 0 && o3 * o4 < 0 {
+                let (ax, ay) = ((a.to.0 - a.from.0) as f64, (a.to.1 - a.from.1) as f64);
+                let (bx, by) = ((b.to.0 - b.from.0) as f64, (b.to.1 - b.from.1) as f64);
+                let (cx, cy) = ((b.from.0 - a.from.0) as f64, (b.from.1 - a.from.1) as f64);
+                let t = (cx * by - cy * bx) / (ax * by - ay * bx);
+                let pt = (
+                    (a.from.0 as f64 + t * ax).round() as i64,
+                    (a.from.1 as f64 + t * ay).round() as i64,
+                );
+                for (k, edge) in [(i, a), (j, b)] {
+                    if pt != edge.from && pt != edge.to {
+                        splits[k].push(pt);
+                    }
+                }
+            }
+        }
+    }
+
+    let mut result = Vec::with_capacity(edges.len());
+    for (edge, mut points) in edges.iter().zip(splits) {
+        points.sort_by(|p, q| edge.param(*p).partial_cmp(&edge.param(*q)).unwrap());
+        points.dedup();
+        points.push(edge.to);
+
+        let mut from = edge.from;
+        let mut t0 = 0.;
+        for pt in points {
+            let t1 = if pt == edge.to { 1. } else { edge.param(pt) };
+            if pt != from {
+                let origin = match edge.origin {
+                    Origin::Line => Origin::Line,
+                    Origin::Curve {
+                        index,
+                        t0: c0,
+                        t1: c1,
+                    } => Origin::Curve {
+                        index,
+                        t0: c0 + (c1 - c0) * t0,
+                        t1: c0 + (c1 - c0) * t1,
+                    },
+                };
+                result.push(Edge {
+                    from,
+                    to: pt,
+                    origin,
+                });
+                from = pt;
+                t0 = t1;
+            }
+        }
+    }
+    result
+}
+
+/// Non-zero winding number of all edges around a point, in grid units.
+fn winding(edges: &[Edge], x: f64, y: f64) -> i32 {
+    let mut winding = 0;
+    for edge in edges {
+        let (ax, ay) = (edge.from.0 as f64, edge.from.1 as f64);
+        let (bx, by) = (edge.to.0 as f64, edge.to.1 as f64);
+        let side = (bx - ax) * (y - ay) - (by - ay) * (x - ax);
+        if ay <= y {
+            if by > y && side > 0. {
+                winding += 1;
+            }
+        } else if by <= y && side < 0. {
+            winding -= 1;
+        }
+    }
+    winding
+}
+
+/// Keeps the edges between filled and unfilled area, oriented so that the
+/// filled area is on their left.
+fn classify(edges: &[Edge]) -> Vec<Edge> {
+    let mut groups: Vec<Group> = Vec::new();
+    let mut index: HashMap<(Vertex, Vertex), usize> = HashMap::new();
+    for edge in edges {
+        let forward = edge.from < edge.to;
+        let key = if forward {
+            (edge.from, edge.to)
+        } else {
+            (edge.to, edge.from)
+        };
+        let direction = if forward { 1 } else { -1 };
+        match index.get(&key) {
+            Some(&i) => groups[i].winding += direction,
+            None => {
+                index.insert(key, groups.len());
+                groups.push(Group {
+                    from: key.0,
+                    to: key.1,
+                    winding: direction,
+                    origin: if forward {
+                        edge.origin
+                    } else {
+                        edge.origin.reversed()
+                    },
+                });
+            }
+        }
+    }
+
+    let mut kept = Vec::new();
+    for group in groups {
+        let (dx, dy) = (
+            (group.to.0 - group.from.0) as f64,
+            (group.to.1 - group.from.1) as f64,
+        );
+        let length = dx.hypot(dy);
+        // a quarter grid cell to the right of the middle of the edge
+        let x = (group.from.0 + group.to.0) as f64 / 2. + dy / length / 4.;
+        let y = (group.from.1 + group.to.1) as f64 / 2. - dx / length / 4.;
+        let around = winding(edges, x, y);
+        let (left, right) = (around + group.winding != 0, around != 0);
+
+        if left && !right {
+            kept.push(Edge {
+                from: group.from,
+                to: group.to,
+                origin: group.origin,
+            });
+        } else if right && !left {
+            kept.push(Edge {
+                from: group.to,
+                to: group.from,
+                origin: group.origin.reversed(),
+            });
+        }
+    }
+    kept
+}
+
+/// Links the boundary edges into closed loops.
+///
+/// Where several loops meet in one vertex, the sharpest left turn is taken,
+/// which keeps areas that only touch each other in separate contours.
+fn chain(edges: &[Edge]) -> Vec<Vec<Edge>> {
+    let mut outgoing: HashMap<Vertex, Vec<usize>> = HashMap::new();
+    for (i, edge) in edges.iter().enumerate() {
+        outgoing.entry(edge.from).or_default().push(i);
+    }
+    let mut used = vec![false; edges.len()];
+    let mut loops = Vec::new();
+
+    for first in 0..edges.len() {
+        if used[first] {
+            continue;
+        }
+        let mut contour = Vec::new();
+        let mut current = first;
+        loop {
+            used[current] = true;
+            contour.push(edges[current]);
+            let edge = &edges[current];
+            let incoming = (
+                (edge.to.0 - edge.from.0) as f64,
+                (edge.to.1 - edge.from.1) as f64,
+            );
+            let next = outgoing
+                .get(&edge.to)
+                .into_iter()
+                .flatten()
+                .copied()
+                .filter(|i| !used[*i] || *i == first)
+                .max_by(|a, b| {
+                    let turn = |i: &usize| {
+                        let e = &edges[*i];
+                        let out = ((e.to.0 - e.from.0) as f64, (e.to.1 - e.from.1) as f64);
+                        (incoming.0 * out.1 - incoming.1 * out.0)
+                            .atan2(incoming.0 * out.0 + incoming.1 * out.1)
+                    };
+                    turn(a).partial_cmp(&turn(b)).unwrap()
+                });
+            match next {
+                Some(i) if i != first => current = i,
+                _ => break,
+            }
+        }
+        loops.push(contour);
+    }
+    loops
+}
+
+/// Signed area of a loop, in grid units; positive when counter-clockwise.
+fn area(contour: &[Edge]) -> f64 {
+    contour
+        .iter()
+        .map(|e| (e.from.0 as f64 * e.to.1 as f64 - e.to.0 as f64 * e.from.1 as f64) / 2.)
+        .sum()
+}
+
+/// Whether two consecutive edges can be drawn as one segment.
+fn continues(a: &Edge, b: &Edge) -> bool {
+    match (a.origin, b.origin) {
+        (Origin::Line, Origin::Line) => {
+            let (ax, ay) = (a.to.0 - a.from.0, a.to.1 - a.from.1);
+            let (bx, by) = (b.to.0 - b.from.0, b.to.1 - b.from.1);
+            orient(a.from, a.to, b.to) == 0 && ax * bx + ay * by > 0
+        }
+        (Origin::Curve { index: i, t1, .. }, Origin::Curve { index: j, t0, .. }) => {
+            i == j && t1 == t0
+        }
+        _ => false,
+    }
+}
+
+/// Control points of the part of a cubic between `t0` and `t1`.
+fn sub_curve(c: &Cubic, t0: f64, t1: f64) -> Cubic {
+    let lerp =
+        |a: (f64, f64), b: (f64, f64), t: f64| (a.0 + (b.0 - a.0) * t, a.1 + (b.1 - a.1) * t);
+    let split = |c: &Cubic, t: f64| -> (Cubic, Cubic) {
+        let (ab, bc, cd) = (
+            lerp(c[0], c[1], t),
+            lerp(c[1], c[2], t),
+            lerp(c[2], c[3], t),
+        );
+        let (abc, bcd) = (lerp(ab, bc, t), lerp(bc, cd, t));
+        let mid = lerp(abc, bcd, t);
+        ([c[0], ab, abc, mid], [mid, bcd, cd, c[3]])
+    };
+
+    let (lo, hi) = if t0 < t1 { (t0, t1) } else { (t1, t0) };
+    let (_, right) = split(c, lo);
+    let (part, _) = split(&right, (hi - lo) / (1. - lo));
+    if t0 < t1 {
+        part
+    } else {
+        [part[3], part[2], part[1], part[0]]
+    }
+}
+
+fn rebuild<F: Float>(outline: &mut Outline<F>, contour: &[Edge], curves: &[Cubic]) {
+    let n = contour.len();
+    // start at a corner, so no segment is cut in two by the contour's start
+    let start = (0..n)
+        .find(|i| !continues(&contour[(i + n - 1) % n], &contour[*i]))
+        .unwrap_or(0);
+    let edges: Vec<&Edge> = contour[start..].iter().chain(&contour[..start]).collect();
+
+    outline.push(PathSegment::Move(unsnap(edges[0].from)));
+    let mut i = 0;
+    while i < n {
+        let first = edges[i];
+        while i + 1 < n && continues(edges[i], edges[i + 1]) {
+            i += 1;
+        }
+        let last = edges[i];
+        i += 1;
+
+        match (first.origin, last.origin) {
+            (Origin::Curve { index, t0, .. }, Origin::Curve { t1, .. }) => {
+                let c = sub_curve(&curves[index], t0, t1);
+                let pt = |(x, y): (f64, f64)| Point::new(F::from(x).unwrap(), F::from(y).unwrap());
+                outline.push(PathSegment::Cubic(pt(c[1]), pt(c[2]), unsnap(last.to)));
+            }
+            _ if i == n => {
+                // the closing line is implied
+            }
+            _ => outline.push(PathSegment::Line(unsnap(last.to))),
+        }
+    }
+    outline.push(PathSegment::Close);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::drawing_command::{Metrics, Point};
+    use crate::font::Font;
+    use crate::outline::PathSegment;
+    use crate::raster::Rasterizer;
+
+    #[test]
+    fn cross_becomes_one_contour() {
+        let font = Font::new(Metrics::<f64>::default());
+        let outline = font.outline(0x253C).remove_overlap();
+        assert_eq!(outline.contours().len(), 1);
+        // twelve corners: the move and eleven lines, the last one implied
+        assert_eq!(outline.contours()[0].segments().len(), 13);
+    }
+
+    #[test]
+    fn touching_quadrants_stay_apart() {
+        let font = Font::new(Metrics::<f64>::default());
+        let outline = font.outline(0x259A).remove_overlap();
+        assert_eq!(outline.contours().len(), 2);
+    }
+
+    #[test]
+    fn curves_survive() {
+        let font = Font::new(Metrics::<f64>::default());
+        let outline = font.outline(0x256D).remove_overlap();
+        let curves = outline
+            .segments()
+            .filter(|s| matches!(s, PathSegment::Cubic(..)))
+            .count();
+        assert_eq!(curves, 2);
+        assert!(outline.points().all(|pt| pt.x >= 220. && pt.y <= 380.));
+        assert!(outline.points().any(|pt| *pt == Point::new(600., 380.)));
+    }
+
+    #[test]
+    fn union_covers_the_same_pixels() {
+        let font = Font::new(Metrics::<f64>::default());
+        for c in (0x2500..=0x259F).filter(|c| Font::<f64>::contains(*c)) {
+            let outline = font.outline(c);
+            let mut before = Rasterizer::new(font.metrics(), 24, 56);
+            outline.replay(&mut before);
+            let mut after = Rasterizer::new(font.metrics(), 24, 56);
+            outline.remove_overlap().replay(&mut after);
+
+            let (before, after) = (before.into_bitmap(), after.into_bitmap());
+            for (a, b) in before.data.iter().zip(after.data.iter()) {
+                assert!((*a as i32 - *b as i32).abs() <= 1, "U+{:04X}", c);
+            }
+        }
+    }
+}
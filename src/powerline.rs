@@ -0,0 +1,121 @@
+//! Recipes for the Powerline separators, U+E0B0 to U+E0B7.
+//!
+//! Like the block elements they fill the block area, so that they meet the
+//! backgrounds of the cells on either side.
+
+use std::ops::{AddAssign, SubAssign};
+
+use num::Float;
+
+use crate::commands::{HalfEllipseBuilder, PolygonBuilder};
+use crate::drawing_command::{Metrics, Point};
+use crate::font::{Configure, Recipe};
+
+/// Corners of the right pointing triangle, counter-clockwise, or of the
+/// chevron along its sides when `stroke` is given.
+fn triangle<F: Float>(m: &Metrics<F>, stroke: Option<F>) -> Vec<Point<F>> {
+    let two = F::one() + F::one();
+    let (x0, y0) = (m.block_origin.x, m.block_origin.y);
+    let (x1, y1) = (m.block_top.x, m.block_top.y);
+    let median = (y0 + y1) / two;
+    let mut points = vec![
+        Point::new(x0, y0),
+        Point::new(x1, median),
+        Point::new(x0, y1),
+    ];
+    if let Some(stroke) = stroke {
+        // the inner sides are the outer ones moved `stroke` across
+        let (width, half) = (x1 - x0, (y1 - y0) / two);
+        let side = width.hypot(half);
+        let (dx, dy) = (stroke * side / half, stroke * side / width);
+        points.extend([
+            Point::new(x0, y1 - dy),
+            Point::new(x1 - dx, median),
+            Point::new(x0, y0 + dy),
+        ]);
+    }
+    points
+}
+
+/// The separator `c`, the left pointing ones being the right pointing ones
+/// turned around the middle of the block area.
+pub(crate) fn separator<F>(c: u32) -> Recipe<F>
+where
+    F: Float + AddAssign + SubAssign + 'static,
+{
+    let left = c & 0b10 != 0;
+    let thin = c & 0b01 != 0;
+    let configure: Configure<F> = if c < 0xE0B4 {
+        Box::new(move |m: &Metrics<F>| {
+            let stroke = thin.then_some(m.stroke);
+            let (x, y) = (
+                m.block_origin.x + m.block_top.x,
+                m.block_origin.y + m.block_top.y,
+            );
+            let mut points = triangle(m, stroke);
+            if left {
+                points
+                    .iter_mut()
+                    .for_each(|p| *p = Point::new(x - p.x, y - p.y));
+            }
+            let mut builder = PolygonBuilder::default();
+            builder.points(points);
+            builder.build().unwrap().into()
+        })
+    } else {
+        Box::new(move |m: &Metrics<F>| {
+            let two = F::one() + F::one();
+            let width = m.block_top.x - m.block_origin.x;
+            let (x, radius_x) = match left {
+                false => (m.block_origin.x, width),
+                true => (m.block_top.x, -width),
+            };
+            let mut builder = HalfEllipseBuilder::default();
+            builder
+                .center(Point::new(x, (m.block_origin.y + m.block_top.y) / two))
+                .radius_x(radius_x)
+                .radius_y(m.block_height / two);
+            if thin {
+                builder.stroke(m.stroke);
+            }
+            builder.build().unwrap().into()
+        })
+    };
+    Recipe::new(c, format!("uni{:04X}", c), vec![configure])
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::drawing_command::Metrics;
+    use crate::font::{Font, PrivateUse};
+
+    #[test]
+    fn separators_are_opt_in() {
+        let font = Font::new(Metrics::<f64>::default());
+        assert!(!font.has_glyph(0xE0B0));
+        let font = font.private_use(PrivateUse::Powerline);
+        assert!(font.has_glyph(0xE0B0));
+        assert!(!Font::<f64>::contains(0xE0B0));
+        assert_eq!(
+            font.code_points()
+                .filter(|c| (0xE000..=0xF8FF).contains(c))
+                .count(),
+            8
+        );
+
+        // separators reach the cell edge they stand on, and the left and
+        // right pointing ones are mirror images
+        for (right, left) in [(0xE0B0, 0xE0B2), (0xE0B1, 0xE0B3), (0xE0B4, 0xE0B6)] {
+            let right = font.render_cell(right, 10, 20, 1);
+            let left = font.render_cell(left, 10, 20, 1);
+            for y in 0..20 {
+                for x in 0..10 {
+                    assert_eq!(right.get(x, y), left.get(9 - x, y));
+                }
+            }
+        }
+        let solid = font.render_cell(0xE0B4, 10, 20, 1);
+        assert!((0..20).all(|y| solid.get(0, y) != 0));
+        assert!((0..9).all(|x| solid.get(x, 9) == 255));
+    }
+}
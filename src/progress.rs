@@ -0,0 +1,177 @@
+//! Recipes for the progress bar and spinner of Fira Code, used by Nerd
+//! Fonts at U+EE00 to U+EE0B.
+//!
+//! The bar is an outline a light stroke thick around a fill, with a gap of
+//! the same width between them. Middle pieces run from edge to edge, so a
+//! row of cells makes a single bar.
+
+use std::ops::{AddAssign, SubAssign};
+
+use num::Float;
+
+use crate::commands::{Box_Builder, HorLineBuilder, RingArcBuilder, VertLineBuilder};
+use crate::drawing_command::{Metrics, Point};
+use crate::font::{Configure, Recipe};
+
+/// Where a piece of the bar is.
+#[derive(Clone, Copy, PartialEq)]
+enum Piece {
+    Start,
+    Middle,
+    End,
+}
+
+/// Left and right ends of a piece of the bar: the middle of the cap, a
+/// stroke away from the edge of the cell, or the edge where it goes on.
+fn ends<F: Float>(m: &Metrics<F>, piece: Piece) -> (F, F) {
+    let cap = m.stroke * F::from(1.5).unwrap();
+    match piece {
+        Piece::Start => (cap, m.width),
+        Piece::Middle => (F::zero(), m.width),
+        Piece::End => (F::zero(), m.width - cap),
+    }
+}
+
+/// Top and bottom lines of a piece of the bar, and its cap.
+fn outline<F>(piece: Piece) -> Vec<Configure<F>>
+where
+    F: Float + AddAssign + SubAssign + 'static,
+{
+    let mut commands: Vec<Configure<F>> = Vec::new();
+    for top in [false, true] {
+        commands.push(Box::new(move |m: &Metrics<F>| {
+            let two = F::one() + F::one();
+            let (left, right) = ends(m, piece);
+            let half = m.width / two - m.stroke / two;
+            let y = if top {
+                m.median + half
+            } else {
+                m.median - half
+            };
+            let (butt_left, butt_right) = match piece {
+                Piece::Start => (F::zero(), m.butt),
+                Piece::Middle => (m.butt, m.butt),
+                Piece::End => (m.butt, F::zero()),
+            };
+            let mut builder = HorLineBuilder::default();
+            builder
+                .start(Point::new(left, y))
+                .end(Point::new(right, y))
+                .stroke(m.stroke)
+                .butt_left(butt_left)
+                .butt_right(butt_right);
+            builder.build().unwrap().into()
+        }));
+    }
+    if piece != Piece::Middle {
+        commands.push(Box::new(move |m: &Metrics<F>| {
+            let two = F::one() + F::one();
+            let (left, right) = ends(m, piece);
+            let x = if piece == Piece::Start { left } else { right };
+            let mut builder = VertLineBuilder::default();
+            builder
+                .start(Point::new(x, m.median - m.width / two))
+                .end(Point::new(x, m.median + m.width / two))
+                .stroke(m.stroke);
+            builder.build().unwrap().into()
+        }));
+    }
+    commands
+}
+
+/// The fill of a piece of the bar, inside the outline.
+fn fill<F>(piece: Piece) -> Configure<F>
+where
+    F: Float + AddAssign + SubAssign + 'static,
+{
+    Box::new(move |m: &Metrics<F>| {
+        let two = F::one() + F::one();
+        let inset = m.stroke * F::from(1.5).unwrap();
+        let (mut left, mut right) = ends(m, piece);
+        if piece == Piece::Start {
+            left += inset;
+        }
+        if piece == Piece::End {
+            right -= inset;
+        }
+        let half = m.width / two - m.stroke * two;
+        let mut builder = Box_Builder::default();
+        builder.start(Point::new(left, m.median - half));
+        builder.end(Point::new(right, m.median + half));
+        builder.build().unwrap().into()
+    })
+}
+
+/// A frame of the spinner: a third of a ring, turned a sixth further round
+/// clockwise in each frame.
+fn spinner<F>(frame: u32) -> Configure<F>
+where
+    F: Float + AddAssign + SubAssign + 'static,
+{
+    Box::new(move |m: &Metrics<F>| {
+        let two = F::one() + F::one();
+        let sixth = F::from(std::f64::consts::FRAC_PI_3).unwrap();
+        // the first frame ends at the top
+        let end = sixth * F::from(1.5 - frame as f64).unwrap();
+        let mut builder = RingArcBuilder::default();
+        builder
+            .center(Point::new(m.width / two, m.median))
+            .radius(m.width / two - m.stroke)
+            .stroke(m.stroke)
+            .start(end - sixth * two)
+            .end(end);
+        builder.build().unwrap().into()
+    })
+}
+
+/// The progress bar piece or spinner frame `c`.
+pub(crate) fn glyph<F>(c: u32) -> Recipe<F>
+where
+    F: Float + AddAssign + SubAssign + 'static,
+{
+    let commands = match c {
+        0xEE00..=0xEE05 => {
+            let piece = [Piece::Start, Piece::Middle, Piece::End][(c - 0xEE00) as usize % 3];
+            let mut commands = outline(piece);
+            if c >= 0xEE03 {
+                commands.push(fill(piece));
+            }
+            commands
+        }
+        _ => vec![spinner(c - 0xEE06)],
+    };
+    Recipe::new(c, format!("uni{:04X}", c), commands)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::drawing_command::Metrics;
+    use crate::font::{Font, PrivateUse};
+
+    #[test]
+    fn pieces_make_one_bar() {
+        let font = Font::new(Metrics::<f64>::default()).private_use(PrivateUse::ProgressBar);
+        assert!(font.has_glyph(0xEE0B));
+
+        // middle pieces continue the ends and each other row by row, the
+        // ends are mirror images
+        for bar in [[0xEE00, 0xEE01, 0xEE02], [0xEE03, 0xEE04, 0xEE05]] {
+            let pieces: Vec<_> = bar
+                .iter()
+                .map(|c| font.render_cell(*c, 10, 20, 1))
+                .collect();
+            for (left, right) in [(0, 1), (1, 1), (1, 2)] {
+                for y in 0..20 {
+                    let (left, right) = (pieces[left].get(9, y), pieces[right].get(0, y));
+                    assert_eq!(left == 0, right == 0, "row {}", y);
+                }
+            }
+            for (y, x) in (0..20).flat_map(|y| (0..10).map(move |x| (y, x))) {
+                assert_eq!(pieces[0].get(x, y), pieces[2].get(9 - x, y));
+            }
+        }
+        let filled = font.render_cell(0xEE04, 10, 20, 1);
+        let empty = font.render_cell(0xEE01, 10, 20, 1);
+        assert!((0..20).any(|y| filled.get(5, y) == 255 && empty.get(5, y) == 0));
+    }
+}
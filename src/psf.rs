@@ -0,0 +1,148 @@
+use std::ops::{AddAssign, SubAssign};
+
+use num::Float;
+
+use crate::font::{CodePointError, Font};
+
+const PSF2_MAGIC: u32 = 0x864A_B572;
+const PSF2_HAS_UNICODE_TABLE: u32 = 1;
+/// Ends the code points of a glyph in the Unicode table.
+const PSF2_SEPARATOR: u8 = 0xFF;
+/// The console only loads fonts of 256 or 512 glyphs.
+const GLYPH_COUNTS: [usize; 2] = [256, 512];
+
+/// Writes the glyphs of a [`Font`] as a PSF2 font, for the Linux console.
+///
+/// Glyphs are rendered with [`Font::render_cell`]. Glyph 0 is a blank
+/// mapped to the space, followed by the glyphs of the chosen code points,
+/// by default [`Font::default_code_points`]; the rest of the 256 or
+/// 512 glyphs are blank and unmapped, for merging into a complete console
+/// font.
+pub struct PsfWriter<'a, F: Float> {
+    font: &'a Font<F>,
+    cell_width: usize,
+    cell_height: usize,
+    thickness: usize,
+    code_points: Vec<u32>,
+}
+
+impl<'a, F> PsfWriter<'a, F>
+where
+    F: Float + Default + AddAssign + SubAssign + 'static,
+{
+    pub fn new(font: &'a Font<F>, cell_width: usize, cell_height: usize) -> PsfWriter<'a, F> {
+        PsfWriter {
+            font,
+            cell_width,
+            cell_height,
+            thickness: font.cell_thickness(cell_width),
+            code_points: font.default_code_points().collect(),
+        }
+    }
+
+    /// The code points to include, at most 511 of them, all with a glyph in
+    /// the font.
+    pub fn code_points(
+        mut self,
+        code_points: impl IntoIterator<Item = u32>,
+    ) -> Result<Self, CodePointError> {
+        let code_points: Vec<u32> = code_points.into_iter().collect();
+        if code_points.len() >= GLYPH_COUNTS[1] {
+            return Err(CodePointError::TooMany(
+                code_points.len(),
+                GLYPH_COUNTS[1] - 1,
+            ));
+        }
+        self.code_points = self.font.select(code_points)?;
+        Ok(self)
+    }
+
+    /// Width of light lines, in pixels.
+    pub fn thickness(mut self, thickness: usize) -> Self {
+        self.thickness = thickness;
+        self
+    }
+
+    pub fn write(&self) -> Vec<u8> {
+        let (width, height) = (self.cell_width, self.cell_height);
+        let glyph_size = width.div_ceil(8) * height;
+        let glyph_count = GLYPH_COUNTS
+            .into_iter()
+            .find(|count| *count > self.code_points.len())
+            .unwrap();
+
+        let mut psf = Vec::new();
+        for v in [
+            PSF2_MAGIC,
+            0,
+            32,
+            PSF2_HAS_UNICODE_TABLE,
+            glyph_count as u32,
+            glyph_size as u32,
+            height as u32,
+            width as u32,
+        ] {
+            psf.extend_from_slice(&v.to_le_bytes());
+        }
+
+        let mut table = vec![b' ', PSF2_SEPARATOR];
+        psf.resize(psf.len() + glyph_size, 0);
+        for c in self.code_points.iter().copied() {
+            let bitmap = self.font.render_cell(c, width, height, self.thickness);
+            psf.extend_from_slice(&bitmap.to_monochrome());
+            let mut utf8 = [0; 4];
+            table.extend_from_slice(char::from_u32(c).unwrap().encode_utf8(&mut utf8).as_bytes());
+            table.push(PSF2_SEPARATOR);
+        }
+        let blanks = glyph_count - 1 - self.code_points.len();
+        psf.resize(psf.len() + blanks * glyph_size, 0);
+        table.resize(table.len() + blanks, PSF2_SEPARATOR);
+
+        psf.extend_from_slice(&table);
+        psf
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::drawing_command::Metrics;
+
+    #[test]
+    fn header_and_table() {
+        let font = Font::new(Metrics::<f64>::default());
+        let psf = PsfWriter::new(&font, 9, 18).write();
+        let field = |i: usize| {
+            u32::from_le_bytes([psf[4 * i], psf[4 * i + 1], psf[4 * i + 2], psf[4 * i + 3]])
+        };
+        assert_eq!(field(0), PSF2_MAGIC);
+        assert_eq!((field(4), field(5), field(6), field(7)), (256, 36, 18, 9));
+
+        let table = &psf[32 + 256 * 36..];
+        assert_eq!(table.iter().filter(|b| **b == PSF2_SEPARATOR).count(), 256);
+        assert!(table.starts_with(b" \xFF\xE2\x94\x80\xFF"));
+
+        let psf = PsfWriter::new(&font, 9, 18)
+            .code_points(0x2580..=0x259F)
+            .unwrap()
+            .write();
+        assert_eq!(psf[16..20], 256u32.to_le_bytes());
+        assert!(psf[32 + 256 * 36..].starts_with(b" \xFF\xE2\x96\x80\xFF"));
+
+        // more than 255 code points take a font of 512 glyphs
+        let psf = PsfWriter::new(&font, 9, 18)
+            .code_points((0x2500..=0x259F).chain(0x1FB00..=0x1FB6F))
+            .unwrap()
+            .write();
+        assert_eq!(psf[16..20], 512u32.to_le_bytes());
+        let table = &psf[32 + 512 * 36..];
+        assert_eq!(table.iter().filter(|b| **b == PSF2_SEPARATOR).count(), 512);
+
+        let writer = PsfWriter::new(&font, 9, 18);
+        let error = writer.code_points(0x2500..=0x2700).err();
+        assert_eq!(error, Some(CodePointError::TooMany(513, 511)));
+        let writer = PsfWriter::new(&font, 9, 18);
+        let error = writer.code_points([0x2500, 'A' as u32]).err();
+        assert_eq!(error, Some(CodePointError::NoGlyph(0x41)));
+    }
+}
@@ -0,0 +1,401 @@
+//! Conversion of cubic curves into quadratic splines, for TrueType outlines.
+//!
+//! This is a port of the `cu2qu` algorithm: a cubic is split into `n` equal
+//! parts and every part is approximated by one quadratic curve, the on-curve
+//! points in between being the midpoints of consecutive control points. `n`
+//! grows until the spline stays within the tolerance of the cubic.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::ops::{Add, AddAssign, Mul, Sub, SubAssign};
+
+use num::Float;
+
+use crate::drawing_command::Point;
+use crate::font::Font;
+use crate::outline::{Contour, Outline, PathSegment};
+
+/// Largest number of quadratic curves a single cubic is split into.
+///
+/// A cubic that does not fit within the tolerance even in this many parts,
+/// as with a tolerance of zero, is split into this many anyway and may stray
+/// further from the original.
+pub const MAX_N: usize = 100;
+
+/// Returned by [`fonts_to_quadratic`] when the outlines of a glyph do not
+/// have the same structure in all fonts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IncompatibleOutlines {
+    pub code_point: u32,
+}
+
+impl fmt::Display for IncompatibleOutlines {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "outlines of U+{:04X} are not compatible",
+            self.code_point
+        )
+    }
+}
+
+impl std::error::Error for IncompatibleOutlines {}
+
+impl<F: Float> Outline<F> {
+    /// Replaces every cubic curve with quadratic curves, staying within
+    /// `tolerance` font units of the original, unless that takes more than
+    /// [`MAX_N`] curves.
+    pub fn to_quadratic(&self, tolerance: F) -> Outline<F> {
+        convert(&[self], tolerance).pop().unwrap()
+    }
+}
+
+/// Converts the whole glyph set of several fonts, e.g. the weight masters of
+/// a family, into quadratic outlines with TrueType contour direction.
+///
+/// Every cubic is split into the same number of quadratic curves in all
+/// fonts, so the results stay point-compatible for interpolation, within
+/// `tolerance` as far as [`MAX_N`] curves allow. Returns the outlines of each
+/// code point, in the order of `fonts`.
+pub fn fonts_to_quadratic<F>(
+    fonts: &[Font<F>],
+    tolerance: F,
+) -> Result<BTreeMap<u32, Vec<Outline<F>>>, IncompatibleOutlines>
+where
+    F: Float + Default + AddAssign + SubAssign + 'static,
+{
+    let mut glyphs = BTreeMap::new();
+    if fonts.is_empty() {
+        return Ok(glyphs);
+    }
+    for c in fonts[0].code_points() {
+        let outlines: Vec<_> = fonts.iter().map(|font| font.outline(c)).collect();
+        if !outlines[1..].iter().all(|o| compatible(&outlines[0], o)) {
+            return Err(IncompatibleOutlines { code_point: c });
+        }
+        // decide on the first font, so all of them are reversed alike
+        let flips = outlines[0].direction_flips(true);
+        let outlines: Vec<_> = outlines
+            .iter()
+            .map(|outline| outline.flip_contours(&flips))
+            .collect();
+        let outlines: Vec<_> = outlines.iter().collect();
+        glyphs.insert(c, convert(&outlines, tolerance));
+    }
+    Ok(glyphs)
+}
+
+fn compatible<F: Float>(a: &Outline<F>, b: &Outline<F>) -> bool {
+    let kind = |s: &PathSegment<F>| std::mem::discriminant(s);
+    a.contours().len() == b.contours().len()
+        && a.contours().iter().zip(b.contours()).all(|(a, b)| {
+            a.segments().len() == b.segments().len()
+                && a.iter().zip(b.iter()).all(|(a, b)| kind(a) == kind(b))
+        })
+}
+
+/// Converts compatible outlines together.
+fn convert<F: Float>(outlines: &[&Outline<F>], tolerance: F) -> Vec<Outline<F>> {
+    let tolerance = tolerance.to_f64().unwrap();
+    let mut converted = vec![Outline::new(); outlines.len()];
+    for (i, contour) in outlines[0].contours().iter().enumerate() {
+        let contours: Vec<&Contour<F>> = outlines.iter().map(|o| &o.contours()[i]).collect();
+        let mut current: Vec<Point<F>> = contours.iter().map(|c| c.start()).collect();
+        for (j, segment) in contour.iter().enumerate() {
+            let segments: Vec<&PathSegment<F>> =
+                contours.iter().map(|c| &c.segments()[j]).collect();
+            if let PathSegment::Cubic(..) = segment {
+                let curves: Vec<[V; 4]> = segments
+                    .iter()
+                    .zip(current.iter())
+                    .map(|(segment, current)| {
+                        let (pt1, pt2, end) = segment.as_cubic(current).unwrap();
+                        [
+                            V::from(current),
+                            V::from(&pt1),
+                            V::from(&pt2),
+                            V::from(&end),
+                        ]
+                    })
+                    .collect();
+                for (outline, spline) in converted
+                    .iter_mut()
+                    .zip(curves_to_quadratic(&curves, tolerance))
+                {
+                    let last = spline.len() - 1;
+                    for k in 1..last {
+                        let end = if k + 1 < last {
+                            (spline[k] + spline[k + 1]) * 0.5
+                        } else {
+                            spline[last]
+                        };
+                        outline.push(PathSegment::Quad(spline[k].point(), end.point()));
+                    }
+                }
+            } else {
+                for (outline, segment) in converted.iter_mut().zip(segments.iter()) {
+                    outline.push(**segment);
+                }
+            }
+            for (current, segment) in current.iter_mut().zip(segments.iter()) {
+                if let Some(end) = segment.end() {
+                    *current = end;
+                }
+            }
+        }
+    }
+    converted
+}
+
+/// Approximates each of the cubics with a quadratic spline of the same number
+/// of curves, `[start, control points.., end]`, within `tolerance` if
+/// [`MAX_N`] curves are enough.
+fn curves_to_quadratic(curves: &[[V; 4]], tolerance: f64) -> Vec<Vec<V>> {
+    let mut splines = vec![Vec::new(); curves.len()];
+    let (mut i, mut last_i, mut n) = (0, 0, 1);
+    loop {
+        match cubic_approx_spline(&curves[i], n, tolerance) {
+            Some(spline) => {
+                splines[i] = spline;
+                i = (i + 1) % curves.len();
+                if i == last_i {
+                    return splines;
+                }
+            }
+            None if n == MAX_N => break,
+            None => {
+                n += 1;
+                last_i = i;
+            }
+        }
+    }
+    // Nothing fits, settle for the finest split.
+    curves
+        .iter()
+        .map(|curve| cubic_approx_spline(curve, MAX_N, f64::INFINITY).unwrap())
+        .collect()
+}
+
+fn cubic_approx_spline(cubic: &[V; 4], n: usize, tolerance: f64) -> Option<Vec<V>> {
+    if n == 1 {
+        return cubic_approx_quadratic(cubic, tolerance);
+    }
+
+    let cubics = split_cubic_into_n(cubic, n);
+    let mut next_q1 = cubic_approx_control(0., &cubics[0]);
+    let mut q2 = cubic[0];
+    let mut d1 = V(0., 0.);
+    let mut spline = vec![cubic[0], next_q1];
+    for i in 1..=n {
+        let [_, c1, c2, c3] = cubics[i - 1];
+        let q0 = q2;
+        let q1 = next_q1;
+        if i < n {
+            next_q1 = cubic_approx_control((i as f64) / (n - 1) as f64, &cubics[i]);
+            spline.push(next_q1);
+            q2 = (q1 + next_q1) * 0.5;
+        } else {
+            q2 = c3;
+        }
+        let d0 = d1;
+        d1 = q2 - c3;
+        if d1.abs() > tolerance
+            || !cubic_farthest_fit_inside(
+                d0,
+                q0 + (q1 - q0) * (2. / 3.) - c1,
+                q2 + (q1 - q2) * (2. / 3.) - c2,
+                d1,
+                tolerance,
+            )
+        {
+            return None;
+        }
+    }
+    spline.push(cubic[3]);
+    Some(spline)
+}
+
+fn cubic_approx_quadratic(cubic: &[V; 4], tolerance: f64) -> Option<Vec<V>> {
+    let q1 = calc_intersect(cubic)?;
+    let (c0, c3) = (cubic[0], cubic[3]);
+    let c1 = c0 + (q1 - c0) * (2. / 3.);
+    let c2 = c3 + (q1 - c3) * (2. / 3.);
+    if !cubic_farthest_fit_inside(
+        V(0., 0.),
+        c1 - cubic[1],
+        c2 - cubic[2],
+        V(0., 0.),
+        tolerance,
+    ) {
+        return None;
+    }
+    Some(vec![c0, q1, c3])
+}
+
+/// Control point of a quadratic approximating the cubic, blending between
+/// the extended start and end tangents.
+fn cubic_approx_control(t: f64, [p0, p1, p2, p3]: &[V; 4]) -> V {
+    let a = *p0 + (*p1 - *p0) * 1.5;
+    let b = *p3 + (*p2 - *p3) * 1.5;
+    a + (b - a) * t
+}
+
+/// Intersection of the start and end tangents of the cubic.
+fn calc_intersect([a, b, c, d]: &[V; 4]) -> Option<V> {
+    let ab = *b - *a;
+    let cd = *d - *c;
+    let p = V(-ab.1, ab.0);
+    let h = p.dot(*a - *c) / p.dot(cd);
+    if h.is_finite() {
+        Some(*c + cd * h)
+    } else {
+        None
+    }
+}
+
+/// Whether the cubic given by its points stays within `tolerance` of the
+/// origin. The control points of the difference of two curves are passed.
+fn cubic_farthest_fit_inside(p0: V, p1: V, p2: V, p3: V, tolerance: f64) -> bool {
+    if p1.abs() <= tolerance && p2.abs() <= tolerance {
+        return true;
+    }
+    let mid = (p0 + (p1 + p2) * 3. + p3) * 0.125;
+    if mid.abs() > tolerance {
+        return false;
+    }
+    let deriv3 = (p3 + p2 - p1 - p0) * 0.125;
+    cubic_farthest_fit_inside(p0, (p0 + p1) * 0.5, mid - deriv3, mid, tolerance)
+        && cubic_farthest_fit_inside(mid, mid + deriv3, (p2 + p3) * 0.5, p3, tolerance)
+}
+
+/// Splits a cubic into `n` parts of equal parameter range.
+fn split_cubic_into_n([p0, p1, p2, p3]: &[V; 4], n: usize) -> Vec<[V; 4]> {
+    let c = (*p1 - *p0) * 3.;
+    let b = (*p2 - *p1) * 3. - c;
+    let a = *p3 - *p0 - c - b;
+    let d = *p0;
+
+    let dt = 1. / n as f64;
+    let (delta_2, delta_3) = (dt * dt, dt * dt * dt);
+    (0..n)
+        .map(|i| {
+            let t1 = i as f64 * dt;
+            let t1_2 = t1 * t1;
+            let a1 = a * delta_3;
+            let b1 = (a * 3. * t1 + b) * delta_2;
+            let c1 = (b * 2. * t1 + c + a * 3. * t1_2) * dt;
+            let d1 = a * t1 * t1_2 + b * t1_2 + c * t1 + d;
+            let q1 = c1 * (1. / 3.) + d1;
+            let q2 = (b1 + c1) * (1. / 3.) + q1;
+            [d1, q1, q2, a1 + d1 + c1 + b1]
+        })
+        .collect()
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct V(f64, f64);
+
+impl V {
+    fn from<F: Float>(pt: &Point<F>) -> V {
+        V(pt.x.to_f64().unwrap(), pt.y.to_f64().unwrap())
+    }
+
+    fn point<F: Float>(self) -> Point<F> {
+        Point::new(F::from(self.0).unwrap(), F::from(self.1).unwrap())
+    }
+
+    fn abs(self) -> f64 {
+        self.0.hypot(self.1)
+    }
+
+    fn dot(self, other: V) -> f64 {
+        self.0 * other.0 + self.1 * other.1
+    }
+}
+
+impl Add for V {
+    type Output = V;
+    fn add(self, other: V) -> V {
+        V(self.0 + other.0, self.1 + other.1)
+    }
+}
+
+impl Sub for V {
+    type Output = V;
+    fn sub(self, other: V) -> V {
+        V(self.0 - other.0, self.1 - other.1)
+    }
+}
+
+impl Mul<f64> for V {
+    type Output = V;
+    fn mul(self, k: f64) -> V {
+        V(self.0 * k, self.1 * k)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::drawing_command::Metrics;
+
+    #[test]
+    fn quarter_circle_within_tolerance() {
+        let k = 0.5523;
+        let curve = [
+            V(0., 100.),
+            V(100. * k, 100.),
+            V(100., 100. * k),
+            V(100., 0.),
+        ];
+        let spline = curves_to_quadratic(&[curve], 0.1).pop().unwrap();
+        assert!(spline.len() > 3);
+        assert_eq!((spline[0], spline[spline.len() - 1]), (curve[0], curve[3]));
+
+        // the quadratic k of n against the same part of the cubic
+        let n = spline.len() - 2;
+        let on_curve = |k: usize| match k {
+            0 => spline[0],
+            k if k == n => spline[n + 1],
+            k => (spline[k] + spline[k + 1]) * 0.5,
+        };
+        let mut farthest: f64 = 0.;
+        for k in 0..n {
+            let (q0, q1, q2) = (on_curve(k), spline[k + 1], on_curve(k + 1));
+            for step in 0..=10 {
+                let t = step as f64 / 10.;
+                let s = (k as f64 + t) / n as f64;
+                let quad = q0 * ((1. - t) * (1. - t)) + q1 * (2. * t * (1. - t)) + q2 * (t * t);
+                let [c0, c1, c2, c3] = curve;
+                let cubic = c0 * (1. - s).powi(3)
+                    + c1 * (3. * s * (1. - s).powi(2))
+                    + c2 * (3. * s * s * (1. - s))
+                    + c3 * s.powi(3);
+                farthest = farthest.max((quad - cubic).abs());
+            }
+        }
+        assert!(farthest <= 0.1, "{}", farthest);
+
+        // past MAX_N curves the tolerance gives way
+        let spline = curves_to_quadratic(&[curve], 0.).pop().unwrap();
+        assert_eq!(spline.len(), MAX_N + 2);
+    }
+
+    #[test]
+    fn masters_stay_compatible() {
+        let light = Metrics::<f64>::default();
+        let bold = Metrics {
+            stroke: 240.,
+            ..Metrics::default()
+        };
+        let fonts = [Font::new(light), Font::new(bold)];
+        let glyphs = fonts_to_quadratic(&fonts, 1.).unwrap();
+        assert_eq!(glyphs.len(), fonts[0].code_points().count());
+        for outlines in glyphs.values() {
+            assert!(compatible(&outlines[0], &outlines[1]));
+            assert!(outlines[0]
+                .segments()
+                .all(|s| !matches!(s, PathSegment::Cubic(..))));
+        }
+    }
+}
@@ -0,0 +1,401 @@
+use std::cell::RefCell;
+
+use num::Float;
+
+use crate::drawing_command::{Canvas, Metrics, Point};
+
+/// Maximum distance, in pixels, between a cubic curve and its flattened polyline.
+const FLATNESS: f64 = 0.05;
+
+/// An 8-bit grayscale coverage bitmap.
+///
+/// Rows are stored top to bottom, `data[y * width + x]` being the coverage of
+/// the pixel at column `x` and row `y`; 0 is empty and 255 fully covered.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Bitmap {
+    pub width: usize,
+    pub height: usize,
+    pub data: Vec<u8>,
+}
+
+impl Bitmap {
+    pub fn get(&self, x: usize, y: usize) -> u8 {
+        self.data[y * self.width + x]
+    }
+
+    /// One bit per pixel, set where at least half of it is covered, most
+    /// significant bit first and each row padded to whole bytes.
+    pub fn to_monochrome(&self) -> Vec<u8> {
+        let row_bytes = self.width.div_ceil(8);
+        let mut bits = vec![0; row_bytes * self.height];
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if self.get(x, y) >= 128 {
+                    bits[y * row_bytes + x / 8] |= 0x80 >> (x % 8);
+                }
+            }
+        }
+        bits
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Edge {
+    x0: f64,
+    y0: f64,
+    x1: f64,
+    y1: f64,
+}
+
+impl Edge {
+    fn top(&self) -> f64 {
+        self.y0.min(self.y1)
+    }
+
+    fn bottom(&self) -> f64 {
+        self.y0.max(self.y1)
+    }
+
+    fn x_at(&self, y: f64) -> f64 {
+        self.x0 + (y - self.y0) * (self.x1 - self.x0) / (self.y1 - self.y0)
+    }
+
+    fn winding(&self) -> i32 {
+        if self.y1 > self.y0 {
+            1
+        } else {
+            -1
+        }
+    }
+}
+
+#[derive(Default)]
+struct Path {
+    edges: Vec<Edge>,
+    start: (f64, f64),
+    current: (f64, f64),
+}
+
+impl Path {
+    fn line_to(&mut self, (x, y): (f64, f64)) {
+        let (x0, y0) = self.current;
+        if y0 != y {
+            self.edges.push(Edge {
+                x0,
+                y0,
+                x1: x,
+                y1: y,
+            });
+        }
+        self.current = (x, y);
+    }
+}
+
+/// A canvas that fills the drawing into a grayscale bitmap.
+///
+/// Outlines are filled with the non-zero winding rule, and every pixel gets
+/// the exact area of the pixel square covered by the filled region. Curves
+/// are flattened into line segments first.
+///
+/// The block area of the metrics, from `block_origin` to `block_top`, is
+/// mapped onto the whole bitmap.
+pub struct Rasterizer {
+    width: usize,
+    height: usize,
+    scale: (f64, f64),
+    origin: (f64, f64),
+    path: RefCell<Path>,
+}
+
+impl Rasterizer {
+    pub fn new<F: Float>(metrics: &Metrics<F>, width: usize, height: usize) -> Rasterizer {
+        let left = metrics.block_origin.x.to_f64().unwrap();
+        let top = metrics.block_top.y.to_f64().unwrap();
+        let glyph_width = metrics.width.to_f64().unwrap();
+        let glyph_height = top - metrics.block_origin.y.to_f64().unwrap();
+
+        Rasterizer {
+            width,
+            height,
+            scale: (width as f64 / glyph_width, height as f64 / glyph_height),
+            origin: (left, top),
+            path: RefCell::new(Path::default()),
+        }
+    }
+
+    /// Converts a point in font units into pixel space, y pointing down.
+    fn transform<F: Float>(&self, pt: &Point<F>) -> (f64, f64) {
+        (
+            (pt.x.to_f64().unwrap() - self.origin.0) * self.scale.0,
+            (self.origin.1 - pt.y.to_f64().unwrap()) * self.scale.1,
+        )
+    }
+
+    pub fn into_bitmap(self) -> Bitmap {
+        let mut path = self.path.into_inner();
+        let start = path.start;
+        path.line_to(start);
+
+        let mut data = vec![0u8; self.width * self.height];
+        // coverage of the row, and the carry added to every cell right of it
+        let mut cells = vec![0f64; self.width + 2];
+        let mut carry = vec![0f64; self.width + 2];
+
+        for row in 0..self.height {
+            let (top, bottom) = (row as f64, row as f64 + 1.);
+            let edges: Vec<&Edge> = path
+                .edges
+                .iter()
+                .filter(|e| e.top() < bottom && e.bottom() > top)
+                .collect();
+            if edges.is_empty() {
+                continue;
+            }
+            cells.iter_mut().for_each(|v| *v = 0.);
+            carry.iter_mut().for_each(|v| *v = 0.);
+
+            // Within each band no edge starts, ends or crosses another one,
+            // so the filled region in it is a set of trapezoids.
+            for (y0, y1) in bands(&edges, top, bottom) {
+                let mid = (y0 + y1) / 2.;
+                let mut active: Vec<(f64, &Edge)> = edges
+                    .iter()
+                    .filter(|e| e.top() <= mid && e.bottom() >= mid)
+                    .map(|e| (e.x_at(mid), *e))
+                    .collect();
+                active.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+
+                let mut winding = 0;
+                let mut left = None;
+                for (_, edge) in active {
+                    let before = winding;
+                    winding += edge.winding();
+                    if before == 0 && winding != 0 {
+                        left = Some(edge);
+                    } else if before != 0 && winding == 0 {
+                        let l = left.take().unwrap();
+                        let (w, h) = (self.width as f64, y1 - y0);
+                        let (la, lb) = (l.x_at(y0).clamp(0., w), l.x_at(y1).clamp(0., w));
+                        let (ra, rb) = (edge.x_at(y0).clamp(0., w), edge.x_at(y1).clamp(0., w));
+                        accumulate(&mut cells, &mut carry, la, lb, h, 1.);
+                        accumulate(&mut cells, &mut carry, ra, rb, h, -1.);
+                    }
+                }
+            }
+
+            let mut acc = 0.;
+            for x in 0..self.width {
+                acc += carry[x];
+                let coverage = (cells[x] + acc).clamp(0., 1.);
+                data[row * self.width + x] = (coverage * 255.).round() as u8;
+            }
+        }
+
+        Bitmap {
+            width: self.width,
+            height: self.height,
+            data,
+        }
+    }
+}
+
+impl<F: Float> Canvas<F> for Rasterizer {
+    fn move_to(&self, pt: &Point<F>) {
+        let pt = self.transform(pt);
+        let mut path = self.path.borrow_mut();
+        let start = path.start;
+        path.line_to(start);
+        path.start = pt;
+        path.current = pt;
+    }
+
+    fn line_to(&self, pt: &Point<F>) {
+        let pt = self.transform(pt);
+        self.path.borrow_mut().line_to(pt);
+    }
+
+    fn curve_to(&self, pt1: &Point<F>, pt2: &Point<F>, ptend: &Point<F>) {
+        let mut path = self.path.borrow_mut();
+        let p0 = path.current;
+        let (p1, p2, p3) = (
+            self.transform(pt1),
+            self.transform(pt2),
+            self.transform(ptend),
+        );
+
+        // Wang's formula for the number of segments within FLATNESS.
+        let dd = |a: (f64, f64), b: (f64, f64), c: (f64, f64)| {
+            (a.0 - 2. * b.0 + c.0).hypot(a.1 - 2. * b.1 + c.1)
+        };
+        let d = dd(p0, p1, p2).max(dd(p1, p2, p3));
+        let n = ((0.75 * d / FLATNESS).sqrt().ceil() as usize).clamp(1, 100);
+
+        for i in 1..=n {
+            let t = i as f64 / n as f64;
+            let mt = 1. - t;
+            let (a, b, c, e) = (mt * mt * mt, 3. * mt * mt * t, 3. * mt * t * t, t * t * t);
+            path.line_to((
+                a * p0.0 + b * p1.0 + c * p2.0 + e * p3.0,
+                a * p0.1 + b * p1.1 + c * p2.1 + e * p3.1,
+            ));
+        }
+    }
+
+    fn close_path(&self) {
+        let mut path = self.path.borrow_mut();
+        let start = path.start;
+        path.line_to(start);
+    }
+}
+
+/// A canvas rounding the ends of every line and curve to whole units, for
+/// drawing in pixels with [`Metrics::for_pixel_cell`].
+///
+/// Edges a whole number of pixels apart stay so, and fractions of the cell,
+/// as in block elements, snap to the nearest pixel. Curve control points are
+/// left as they are.
+pub(crate) struct PixelGrid<C>(pub C);
+
+impl<F: Float, C: Canvas<F>> Canvas<F> for PixelGrid<C> {
+    fn move_to(&self, pt: &Point<F>) {
+        self.0.move_to(&snap(pt))
+    }
+
+    fn line_to(&self, pt: &Point<F>) {
+        self.0.line_to(&snap(pt))
+    }
+
+    fn curve_to(&self, pt1: &Point<F>, pt2: &Point<F>, ptend: &Point<F>) {
+        self.0.curve_to(pt1, pt2, &snap(ptend))
+    }
+
+    fn close_path(&self) {
+        self.0.close_path()
+    }
+}
+
+fn snap<F: Float>(pt: &Point<F>) -> Point<F> {
+    Point::new(pt.x.round(), pt.y.round())
+}
+
+/// Splits the pixel row `top..bottom` at every y where an edge starts, ends
+/// or crosses another edge.
+fn bands(edges: &[&Edge], top: f64, bottom: f64) -> Vec<(f64, f64)> {
+    let mut ys = vec![top, bottom];
+    for (i, a) in edges.iter().enumerate() {
+        ys.extend([a.y0, a.y1].iter().filter(|y| **y > top && **y < bottom));
+        for b in &edges[i + 1..] {
+            let (y0, y1) = (
+                a.top().max(b.top()).max(top),
+                a.bottom().min(b.bottom()).min(bottom),
+            );
+            if y0 >= y1 {
+                continue;
+            }
+            let (d0, d1) = (a.x_at(y0) - b.x_at(y0), a.x_at(y1) - b.x_at(y1));
+            if d0 * d1 < 0. {
+                ys.push(y0 + (y1 - y0) * d0 / (d0 - d1));
+            }
+        }
+    }
+    ys.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    ys.dedup();
+    ys.windows(2)
+        .filter(|w| w[1] - w[0] > 1e-9)
+        .map(|w| (w[0], w[1]))
+        .collect()
+}
+
+/// Adds the area right of an edge, going from `x0` to `x1` over a band of
+/// height `h`, to the cells of a row.
+fn accumulate(cells: &mut [f64], carry: &mut [f64], x0: f64, x1: f64, h: f64, sign: f64) {
+    let (lo, hi) = if x0 < x1 { (x0, x1) } else { (x1, x0) };
+    let mut column = lo.floor() as usize;
+    loop {
+        let (from, to) = (lo.max(column as f64), hi.min(column as f64 + 1.));
+        let part = if hi > lo {
+            h * (to - from) / (hi - lo)
+        } else {
+            h
+        };
+        cells[column] += sign * part * (column as f64 + 1. - (from + to) / 2.);
+        carry[column + 1] += sign * part;
+        column += 1;
+        if column as f64 >= hi {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Rasterizer;
+    use crate::drawing_command::Metrics;
+    use crate::font::Font;
+
+    #[test]
+    fn full_block_is_opaque() {
+        let font = Font::new(Metrics::<f32>::default());
+        let bitmap = font.rasterize(0x2588, 6, 14);
+        assert!(bitmap.data.iter().all(|v| *v == 255));
+    }
+
+    #[test]
+    fn half_blocks_split_the_cell() {
+        let font = Font::new(Metrics::<f32>::default());
+        let bitmap = font.rasterize(0x258C, 6, 14);
+        for y in 0..14 {
+            assert_eq!(&bitmap.data[y * 6..y * 6 + 6], &[255, 255, 255, 0, 0, 0]);
+        }
+    }
+
+    #[test]
+    fn double_lines_meet_in_corners() {
+        let font = Font::new(Metrics::<f32>::default());
+        let (w, h) = (20, 40);
+        let (vert, hor) = (font.rasterize(0x2551, w, h), font.rasterize(0x2550, w, h));
+        let corner = font.rasterize(0x2554, w, h);
+        // two strokes with a gap between them, which the corner continues
+        let row = &vert.data[h * 3 / 4 * w..(h * 3 / 4 + 1) * w];
+        let runs = row.windows(2).filter(|p| p[0] == 0 && p[1] != 0).count();
+        assert_eq!(runs, 2);
+        assert_eq!(&corner.data[h * 3 / 4 * w..(h * 3 / 4 + 1) * w], row);
+        for y in 0..h {
+            assert_eq!(corner.data[y * w + w - 1], hor.data[y * w + w - 1]);
+        }
+    }
+
+    #[test]
+    fn cells_snap_to_pixels() {
+        let font = Font::new(Metrics::<f64>::default());
+        // all but the arcs, diagonals and shades
+        let straight = (0x2500..=0x256C)
+            .chain(0x2574..=0x2590)
+            .chain(0x2594..=0x259F);
+        for c in straight {
+            let bitmap = font.render_cell(c, 9, 18, 1);
+            assert!(bitmap.data.iter().all(|v| *v == 0 || *v == 255), "{:X}", c);
+        }
+
+        let cross = font.render_cell(0x253C, 9, 18, 1);
+        assert_eq!(cross.data.iter().filter(|v| **v == 255).count(), 9 + 18 - 1);
+    }
+
+    #[test]
+    fn arcs_add_to_overlapping_lines() {
+        let font = Font::new(Metrics::<f64>::default());
+        let (w, h) = (20, 40);
+        let line = font.rasterize(0x2502, w, h);
+        // winding the other way round, an arc would cut the line out
+        for c in 0x256D..=0x2570 {
+            let arc = font.rasterize(c, w, h);
+            let rasterizer = Rasterizer::new(font.metrics(), w, h);
+            font.draw_to(c, &rasterizer);
+            font.draw_to(0x2502, &rasterizer);
+            let both = rasterizer.into_bitmap();
+            for i in 0..w * h {
+                assert!(both.data[i] >= arc.data[i].max(line.data[i]), "{:X}", c);
+            }
+        }
+    }
+}
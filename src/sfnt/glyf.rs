@@ -0,0 +1,177 @@
+use num::Float;
+
+use super::Put;
+use crate::outline::{Outline, PathSegment};
+
+const ON_CURVE: u8 = 0x01;
+const X_SHORT: u8 = 0x02;
+const Y_SHORT: u8 = 0x04;
+const REPEAT: u8 = 0x08;
+const X_SAME_OR_POSITIVE: u8 = 0x10;
+const Y_SAME_OR_POSITIVE: u8 = 0x20;
+
+/// A point of a TrueType contour, in integer font units.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct GlyphPoint {
+    pub x: i16,
+    pub y: i16,
+    pub on_curve: bool,
+}
+
+/// A simple glyph as stored in the `glyf` table.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct Glyph {
+    pub contours: Vec<Vec<GlyphPoint>>,
+}
+
+impl Glyph {
+    /// Rounds an outline to integer coordinates. Cubic curves are converted
+    /// to quadratic ones first, within one font unit.
+    pub fn from_outline<F: Float>(outline: &Outline<F>) -> Glyph {
+        let round = |v: F| {
+            v.round()
+                .to_i16()
+                .unwrap_or(if v > F::zero() { i16::MAX } else { i16::MIN })
+        };
+        let point = |pt: &crate::drawing_command::Point<F>, on_curve| GlyphPoint {
+            x: round(pt.x),
+            y: round(pt.y),
+            on_curve,
+        };
+
+        let mut contours = Vec::new();
+        for contour in outline.to_quadratic(F::one()).contours() {
+            let mut points = vec![point(&contour.start(), true)];
+            for segment in contour.iter() {
+                match segment {
+                    PathSegment::Line(end) => points.push(point(end, true)),
+                    PathSegment::Quad(pt1, end) => {
+                        points.push(point(pt1, false));
+                        points.push(point(end, true));
+                    }
+                    _ => {}
+                }
+            }
+            points.dedup();
+            if points.len() > 1 && points.last() == points.first() {
+                points.pop();
+            }
+            let points = drop_implied(points);
+            if points.len() > 1 {
+                contours.push(points);
+            }
+        }
+        Glyph { contours }
+    }
+
+    pub fn num_points(&self) -> usize {
+        self.contours.iter().map(Vec::len).sum()
+    }
+
+    /// `(x_min, y_min, x_max, y_max)`, or `None` for an empty glyph.
+    pub fn bounds(&self) -> Option<(i16, i16, i16, i16)> {
+        let mut points = self.contours.iter().flatten();
+        let first = points.next()?;
+        Some(points.fold(
+            (first.x, first.y, first.x, first.y),
+            |(x0, y0, x1, y1), p| (x0.min(p.x), y0.min(p.y), x1.max(p.x), y1.max(p.y)),
+        ))
+    }
+
+    /// The glyph record; empty glyphs take no space at all.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut data = Vec::new();
+        let (x_min, y_min, x_max, y_max) = match self.bounds() {
+            Some(bounds) => bounds,
+            None => return data,
+        };
+        data.put_i16(self.contours.len() as i16);
+        data.put_i16(x_min);
+        data.put_i16(y_min);
+        data.put_i16(x_max);
+        data.put_i16(y_max);
+        let mut end = 0;
+        for contour in self.contours.iter() {
+            end += contour.len();
+            data.put_u16(end as u16 - 1);
+        }
+        // no instructions
+        data.put_u16(0);
+
+        let (mut flags, mut xs, mut ys) = (Vec::new(), Vec::new(), Vec::new());
+        let (mut x, mut y) = (0i16, 0i16);
+        for point in self.contours.iter().flatten() {
+            let mut flag = if point.on_curve { ON_CURVE } else { 0 };
+            flag |= coordinate(
+                point.x.wrapping_sub(x),
+                X_SHORT,
+                X_SAME_OR_POSITIVE,
+                &mut xs,
+            );
+            flag |= coordinate(
+                point.y.wrapping_sub(y),
+                Y_SHORT,
+                Y_SAME_OR_POSITIVE,
+                &mut ys,
+            );
+            flags.push(flag);
+            x = point.x;
+            y = point.y;
+        }
+
+        let mut i = 0;
+        while i < flags.len() {
+            let run = flags[i..]
+                .iter()
+                .take(256)
+                .take_while(|f| **f == flags[i])
+                .count();
+            if run > 1 {
+                data.put_u8(flags[i] | REPEAT);
+                data.put_u8(run as u8 - 1);
+            } else {
+                data.put_u8(flags[i]);
+            }
+            i += run;
+        }
+        data.extend_from_slice(&xs);
+        data.extend_from_slice(&ys);
+        data
+    }
+}
+
+/// Writes a coordinate delta in its shortest form, returning its flags.
+fn coordinate(delta: i16, short: u8, same_or_positive: u8, data: &mut Vec<u8>) -> u8 {
+    if delta == 0 {
+        same_or_positive
+    } else if delta.unsigned_abs() <= 255 {
+        data.put_u8(delta.unsigned_abs() as u8);
+        if delta > 0 {
+            short | same_or_positive
+        } else {
+            short
+        }
+    } else {
+        data.put_i16(delta);
+        0
+    }
+}
+
+/// Drops on-curve points that lie exactly halfway between two off-curve
+/// points, as TrueType implies them.
+fn drop_implied(points: Vec<GlyphPoint>) -> Vec<GlyphPoint> {
+    let n = points.len();
+    (0..n)
+        .filter(|i| {
+            let (prev, point, next) = (points[(i + n - 1) % n], points[*i], points[(i + 1) % n]);
+            // keep the start, so contours still begin on the curve
+            *i == 0
+                || !point.on_curve
+                || prev.on_curve
+                || next.on_curve
+                || prev.x as i32 + next.x as i32 != 2 * point.x as i32
+                || prev.y as i32 + next.y as i32 != 2 * point.y as i32
+        })
+        .map(|i| points[i])
+        .collect()
+}
@@ -0,0 +1,141 @@
+//! Reading and writing of sfnt font files (TrueType and OpenType).
+
+mod glyf;
+mod patch;
+mod read;
+mod write;
+
+use std::fmt;
+
+pub use patch::FontPatcher;
+pub use read::FontFile;
+pub use write::TrueTypeWriter;
+
+/// Magic number of the `head` table.
+const HEAD_MAGIC: u32 = 0x5F0F_3CF5;
+/// The whole font sums up to this, with `head.checkSumAdjustment` in place.
+const CHECKSUM_MAGIC: u32 = 0xB1B0_AFBA;
+/// Offset of `checkSumAdjustment` within the `head` table.
+const CHECKSUM_ADJUSTMENT: usize = 8;
+
+/// Why a font file could not be read.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SfntError {
+    /// Not a TrueType or OpenType font file.
+    UnknownFormat,
+    /// The data ends within a table or structure.
+    Truncated,
+    MissingTable([u8; 4]),
+    Unsupported(&'static str),
+}
+
+impl fmt::Display for SfntError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SfntError::UnknownFormat => write!(f, "not a TrueType or OpenType font"),
+            SfntError::Truncated => write!(f, "font file is truncated"),
+            SfntError::MissingTable(tag) => {
+                write!(f, "font has no `{}` table", String::from_utf8_lossy(tag))
+            }
+            SfntError::Unsupported(what) => write!(f, "{} are not supported", what),
+        }
+    }
+}
+
+impl std::error::Error for SfntError {}
+
+/// Appends big-endian values to a byte buffer.
+pub(crate) trait Put {
+    fn put_u8(&mut self, v: u8);
+    fn put_u16(&mut self, v: u16);
+    fn put_i16(&mut self, v: i16);
+    fn put_u32(&mut self, v: u32);
+    fn put_i64(&mut self, v: i64);
+}
+
+impl Put for Vec<u8> {
+    fn put_u8(&mut self, v: u8) {
+        self.push(v);
+    }
+
+    fn put_u16(&mut self, v: u16) {
+        self.extend_from_slice(&v.to_be_bytes());
+    }
+
+    fn put_i16(&mut self, v: i16) {
+        self.extend_from_slice(&v.to_be_bytes());
+    }
+
+    fn put_u32(&mut self, v: u32) {
+        self.extend_from_slice(&v.to_be_bytes());
+    }
+
+    fn put_i64(&mut self, v: i64) {
+        self.extend_from_slice(&v.to_be_bytes());
+    }
+}
+
+/// Sum of the data as big-endian `u32`s, zero padded to a multiple of four.
+pub(crate) fn checksum(data: &[u8]) -> u32 {
+    data.chunks(4).fold(0u32, |sum, chunk| {
+        let mut word = [0u8; 4];
+        word[..chunk.len()].copy_from_slice(chunk);
+        sum.wrapping_add(u32::from_be_bytes(word))
+    })
+}
+
+/// `searchRange`, `entrySelector` and `rangeShift` for a binary search over
+/// `count` items of `size` bytes.
+pub(crate) fn search_params(count: u32, size: u32) -> (u16, u16, u16) {
+    let mut entry_selector = 0;
+    while 2u32.pow(entry_selector + 1) <= count {
+        entry_selector += 1;
+    }
+    let search_range = 2u32.pow(entry_selector) * size;
+    (
+        search_range as u16,
+        entry_selector as u16,
+        (count * size - search_range) as u16,
+    )
+}
+
+/// Lays out the tables into a font file, filling in the table directory
+/// and the checksums.
+///
+/// Tables must be sorted by tag. `head` has its `checkSumAdjustment` set.
+pub(crate) fn assemble(tables: &[([u8; 4], Vec<u8>)]) -> Vec<u8> {
+    let count = tables.len() as u16;
+    let (search_range, entry_selector, range_shift) = search_params(count as u32, 16);
+
+    let mut font = Vec::new();
+    font.put_u32(0x0001_0000);
+    font.put_u16(count);
+    font.put_u16(search_range);
+    font.put_u16(entry_selector);
+    font.put_u16(range_shift);
+
+    let mut offset = 12 + 16 * tables.len();
+    let mut head = None;
+    for (tag, data) in tables {
+        if tag == b"head" {
+            head = Some(offset);
+        }
+        font.extend_from_slice(tag);
+        font.put_u32(checksum(data));
+        font.put_u32(offset as u32);
+        font.put_u32(data.len() as u32);
+        offset += (data.len() + 3) & !3;
+    }
+    for (_, data) in tables {
+        font.extend_from_slice(data);
+        font.resize((font.len() + 3) & !3, 0);
+    }
+
+    if let Some(head) = head {
+        let at = head + CHECKSUM_ADJUSTMENT;
+        font[at..at + 4].copy_from_slice(&[0; 4]);
+        let adjustment = CHECKSUM_MAGIC.wrapping_sub(checksum(&font));
+        font[at..at + 4].copy_from_slice(&adjustment.to_be_bytes());
+    }
+    font
+}
@@ -0,0 +1,398 @@
+use std::borrow::Cow;
+use std::ops::{AddAssign, SubAssign};
+
+use num::Float;
+
+use super::glyf::Glyph;
+use super::write::{cmap, truetype_glyph, unicode_ranges};
+use super::{assemble, FontFile, Put, SfntError};
+use crate::font::{CodePointError, Font};
+use crate::ufo::{MergePolicy, MergeReport};
+
+/// Tables caching per-glyph data, dropped rather than extended, and the
+/// signature, which no longer matches.
+const DROPPED: [&[u8; 4]; 3] = [b"DSIG", b"LTSH", b"hdmx"];
+
+/// Adds the glyphs of a [`Font`] to a compiled TrueType font.
+///
+/// Glyphs are drawn for the chosen code points, by default
+/// [`Font::default_code_points`]. Glyphs for new code points are appended,
+/// glyphs the font already maps are replaced or kept according to the
+/// policy. Only fonts with `glyf`
+/// outlines can be patched; the metrics of the `Font` should match the
+/// font, see [`FontFile::metrics`].
+pub struct FontPatcher<'a, F: Float> {
+    font: &'a Font<F>,
+    policy: MergePolicy,
+    code_points: Vec<u32>,
+}
+
+impl<'a, F> FontPatcher<'a, F>
+where
+    F: Float + Default + AddAssign + SubAssign + 'static,
+{
+    /// A patcher keeping the glyphs the font already has.
+    pub fn new(font: &'a Font<F>) -> FontPatcher<'a, F> {
+        FontPatcher {
+            font,
+            policy: MergePolicy::Keep,
+            code_points: font.default_code_points().collect(),
+        }
+    }
+
+    /// The code points to draw, all with a glyph in the `Font`.
+    pub fn code_points(
+        mut self,
+        code_points: impl IntoIterator<Item = u32>,
+    ) -> Result<Self, CodePointError> {
+        self.code_points = self.font.select(code_points)?;
+        Ok(self)
+    }
+
+    pub fn policy(mut self, policy: MergePolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Returns the patched font file.
+    pub fn patch(&self, data: &[u8]) -> Result<(Vec<u8>, MergeReport), SfntError> {
+        let file = FontFile::parse(data)?;
+        if file.has_cff() {
+            return Err(SfntError::Unsupported("fonts with CFF outlines"));
+        }
+        if file.table(b"fvar").is_some() {
+            return Err(SfntError::Unsupported("variable fonts"));
+        }
+
+        let head = file.require(b"head")?;
+        let glyf = file.require(b"glyf")?;
+        let loca = file.require(b"loca")?;
+        let num_glyphs = file.num_glyphs()? as usize;
+        let long_loca = head.i16(50)? != 0;
+        let mut glyphs = Vec::with_capacity(num_glyphs);
+        for i in 0..num_glyphs {
+            let (start, end) = if long_loca {
+                (loca.u32(4 * i)? as usize, loca.u32(4 * i + 4)? as usize)
+            } else {
+                (
+                    2 * loca.u16(2 * i)? as usize,
+                    2 * loca.u16(2 * i + 2)? as usize,
+                )
+            };
+            glyphs.push(glyf.slice(start, end.saturating_sub(start))?.to_vec());
+        }
+        let mut metrics = self.horizontal_metrics(&file)?;
+
+        let mut char_map = file.char_map()?;
+        let mut report = MergeReport::default();
+        let mut names = Vec::new();
+        let advance = self.font.metrics().width.round().to_u16().unwrap_or(0);
+        let (mut max_points, mut max_contours) = (0, 0);
+        let mut bounds = Vec::new();
+        for c in self.code_points.iter().copied() {
+            let gid = match (char_map.get(&c), self.policy) {
+                (Some(_), MergePolicy::Keep) => {
+                    report.kept.push(c);
+                    continue;
+                }
+                (Some(gid), MergePolicy::Replace) => {
+                    report.replaced.push(c);
+                    *gid as usize
+                }
+                (None, _) => {
+                    if glyphs.len() == u16::MAX as usize {
+                        return Err(SfntError::Unsupported("fonts with 65535 glyphs"));
+                    }
+                    report.added.push(c);
+                    names.push(Font::<F>::glyph_name(c));
+                    glyphs.push(Vec::new());
+                    metrics.push((0, 0));
+                    char_map.insert(c, glyphs.len() as u16 - 1);
+                    glyphs.len() - 1
+                }
+            };
+            let glyph: Glyph = truetype_glyph(self.font, c);
+            max_points = max_points.max(glyph.num_points() as u16);
+            max_contours = max_contours.max(glyph.contours.len() as u16);
+            metrics[gid] = (advance, glyph.bounds().map_or(0, |b| b.0));
+            bounds.extend(glyph.bounds());
+            glyphs[gid] = glyph.encode();
+        }
+
+        let (mut new_glyf, mut new_loca) = (Vec::new(), Vec::new());
+        for glyph in glyphs.iter() {
+            new_loca.put_u32(new_glyf.len() as u32);
+            new_glyf.extend_from_slice(glyph);
+            new_glyf.resize((new_glyf.len() + 3) & !3, 0);
+        }
+        new_loca.put_u32(new_glyf.len() as u32);
+
+        let mut hmtx = Vec::new();
+        for (advance, lsb) in metrics.iter() {
+            hmtx.put_u16(*advance);
+            hmtx.put_i16(*lsb);
+        }
+
+        let mut new_head = head.0.to_vec();
+        for (at, bound, grow) in [(36, 0, false), (38, 1, false), (40, 2, true), (42, 3, true)] {
+            let mut v = head.i16(at)?;
+            for b in bounds.iter() {
+                let b = [b.0, b.1, b.2, b.3][bound];
+                v = if grow { v.max(b) } else { v.min(b) };
+            }
+            new_head[at..at + 2].copy_from_slice(&v.to_be_bytes());
+        }
+        new_head[50..52].copy_from_slice(&1i16.to_be_bytes());
+
+        let hhea = file.require(b"hhea")?;
+        let mut new_hhea = hhea.slice(0, 36)?.to_vec();
+        let widest = hhea.u16(10)?.max(advance);
+        new_hhea[10..12].copy_from_slice(&widest.to_be_bytes());
+        let mut min_lsb = hhea.i16(12)?;
+        let mut min_rsb = hhea.i16(14)?;
+        let mut max_extent = hhea.i16(16)?;
+        for b in bounds.iter() {
+            min_lsb = min_lsb.min(b.0);
+            min_rsb = min_rsb.min(advance as i16 - b.2);
+            max_extent = max_extent.max(b.2);
+        }
+        new_hhea[12..14].copy_from_slice(&min_lsb.to_be_bytes());
+        new_hhea[14..16].copy_from_slice(&min_rsb.to_be_bytes());
+        new_hhea[16..18].copy_from_slice(&max_extent.to_be_bytes());
+        new_hhea[34..36].copy_from_slice(&(glyphs.len() as u16).to_be_bytes());
+
+        let maxp = file.require(b"maxp")?;
+        let mut new_maxp = maxp.0.to_vec();
+        new_maxp[4..6].copy_from_slice(&(glyphs.len() as u16).to_be_bytes());
+        if maxp.u32(0)? >= 0x0001_0000 {
+            let points = maxp.u16(6)?.max(max_points);
+            let contours = maxp.u16(8)?.max(max_contours);
+            new_maxp[6..8].copy_from_slice(&points.to_be_bytes());
+            new_maxp[8..10].copy_from_slice(&contours.to_be_bytes());
+        }
+
+        let mut tables: Vec<([u8; 4], Vec<u8>)> = Vec::new();
+        for tag in file.tags() {
+            if DROPPED.contains(&tag) {
+                continue;
+            }
+            let data = match tag {
+                b"cmap" => cmap(&char_map, variations(&file)?)?,
+                b"glyf" => std::mem::take(&mut new_glyf),
+                b"loca" => std::mem::take(&mut new_loca),
+                b"hmtx" => std::mem::take(&mut hmtx),
+                b"head" => std::mem::take(&mut new_head),
+                b"hhea" => std::mem::take(&mut new_hhea),
+                b"maxp" => std::mem::take(&mut new_maxp),
+                b"OS/2" => os2(&file, &self.code_points)?,
+                b"post" => post(&file, &names, num_glyphs)?,
+                b"vmtx" => vmtx(&file, glyphs.len() - num_glyphs)?,
+                _ => file.table(tag).unwrap().to_vec(),
+            };
+            tables.push((*tag, data));
+        }
+        tables.sort_by_key(|(tag, _)| *tag);
+        Ok((assemble(&tables), report))
+    }
+
+    /// Advance width and left side bearing of every glyph.
+    fn horizontal_metrics(&self, file: &FontFile) -> Result<Vec<(u16, i16)>, SfntError> {
+        let num_metrics = file.require(b"hhea")?.u16(34)? as usize;
+        let hmtx = file.require(b"hmtx")?;
+        let advances = file.advance_widths()?;
+        let mut metrics = Vec::with_capacity(advances.len());
+        for (i, advance) in advances.into_iter().enumerate() {
+            let lsb = if i < num_metrics {
+                hmtx.i16(4 * i + 2)?
+            } else {
+                hmtx.i16(4 * num_metrics + 2 * (i - num_metrics))?
+            };
+            metrics.push((advance, lsb));
+        }
+        Ok(metrics)
+    }
+}
+
+/// The `OS/2` table also claiming the Unicode ranges of `code_points`, with
+/// the first and last character indices widened to cover them.
+fn os2(file: &FontFile, code_points: &[u32]) -> Result<Vec<u8>, SfntError> {
+    let os2 = file.require(b"OS/2")?;
+    let mut table = os2.0.to_vec();
+    for (i, range) in unicode_ranges(code_points).into_iter().enumerate() {
+        let at = 42 + 4 * i;
+        let range = os2.u32(at)? | range;
+        table[at..at + 4].copy_from_slice(&range.to_be_bytes());
+    }
+    // usFirstCharIndex and usLastCharIndex, which stop at U+FFFF
+    if let (Some(first), Some(last)) = (code_points.iter().min(), code_points.iter().max()) {
+        let first = os2.u16(64)?.min(*first.min(&0xFFFF) as u16);
+        let last = os2.u16(66)?.max(*last.min(&0xFFFF) as u16);
+        table[64..66].copy_from_slice(&first.to_be_bytes());
+        table[66..68].copy_from_slice(&last.to_be_bytes());
+    }
+    Ok(table)
+}
+
+/// The format 14 `cmap` subtable, if there is one.
+fn variations<'a>(file: &FontFile<'a>) -> Result<Option<&'a [u8]>, SfntError> {
+    let cmap = file.require(b"cmap")?;
+    for i in 0..cmap.u16(2)? as usize {
+        let offset = cmap.u32(8 + 8 * i)? as usize;
+        if cmap.u16(4 + 8 * i)? == 0 && cmap.u16(6 + 8 * i)? == 5 && cmap.u16(offset)? == 14 {
+            let length = cmap.u32(offset + 2)? as usize;
+            return cmap.slice(offset, length).map(Some);
+        }
+    }
+    Ok(None)
+}
+
+/// The `post` table with names for the appended glyphs. Tables that can't
+/// name them are turned into format 3, which has no names at all.
+fn post(file: &FontFile, names: &[Cow<str>], num_glyphs: usize) -> Result<Vec<u8>, SfntError> {
+    let post = file.require(b"post")?;
+    if names.is_empty() {
+        return Ok(post.0.to_vec());
+    }
+    let mut table = post.slice(0, 32)?.to_vec();
+    if post.u32(0)? != 0x0002_0000 {
+        table[0..4].copy_from_slice(&0x0003_0000u32.to_be_bytes());
+        return Ok(table);
+    }
+
+    let mut indices = Vec::with_capacity(num_glyphs + names.len());
+    for i in 0..post.u16(32)? as usize {
+        indices.push(post.u16(34 + 2 * i)?);
+    }
+    let strings = post.slice(
+        34 + 2 * indices.len(),
+        post.0.len() - 34 - 2 * indices.len(),
+    )?;
+    let mut count = 0;
+    let mut at = 0;
+    while at < strings.len() {
+        at += 1 + strings[at] as usize;
+        count += 1;
+    }
+    for i in 0..names.len() {
+        indices.push(258 + count + i as u16);
+    }
+
+    table.put_u16(indices.len() as u16);
+    for index in indices {
+        table.put_u16(index);
+    }
+    table.extend_from_slice(strings);
+    for name in names {
+        table.put_u8(name.len() as u8);
+        table.extend_from_slice(name.as_bytes());
+    }
+    Ok(table)
+}
+
+/// The `vmtx` table with a zero top side bearing for each appended glyph.
+fn vmtx(file: &FontFile, appended: usize) -> Result<Vec<u8>, SfntError> {
+    let mut table = file.require(b"vmtx")?.0.to_vec();
+    table.resize(table.len() + 2 * appended, 0);
+    Ok(table)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::drawing_command::Metrics;
+    use crate::font::PrivateUse;
+    use crate::sfnt::TrueTypeWriter;
+
+    #[test]
+    fn adds_missing_glyphs() {
+        let font = Font::new(Metrics::<f64>::default());
+        let data = TrueTypeWriter::new(&font, "Box Drawing").write();
+        let count = FontFile::parse(&data).unwrap().num_glyphs().unwrap();
+
+        // the Powerline glyphs are missing from the font
+        let font = font.private_use(PrivateUse::Powerline);
+        let (patched, report) = FontPatcher::new(&font).patch(&data).unwrap();
+        assert_eq!(report.added, (0xE0B0..=0xE0B7).collect::<Vec<_>>());
+        assert_eq!(report.kept.len(), count as usize - 1);
+
+        let file = FontFile::parse(&patched).unwrap();
+        assert_eq!(file.num_glyphs().unwrap(), count + 8);
+        let char_map = file.char_map().unwrap();
+        let advances = file.advance_widths().unwrap();
+        let face = ttf_parser::Face::parse(&patched, 0).unwrap();
+        for (i, c) in (0xE0B0..=0xE0B7).enumerate() {
+            let gid = char_map[&c];
+            assert_eq!(gid, count + i as u16);
+            assert_eq!(advances[gid as usize], 600);
+            let glyph = ttf_parser::GlyphId(gid);
+            assert_eq!(face.glyph_name(glyph), Some(&*Font::<f64>::glyph_name(c)));
+            assert!(face.glyph_bounding_box(glyph).is_some());
+        }
+        assert_eq!(
+            super::super::checksum(&patched),
+            super::super::CHECKSUM_MAGIC
+        );
+    }
+
+    #[test]
+    fn widens_the_os2_ranges() {
+        let font = Font::new(Metrics::<f64>::default());
+        let mut data = TrueTypeWriter::new(&font, "Box Drawing").write();
+        // a font of only the light and heavy lines, as far as its `OS/2`
+        // table tells
+        let file = FontFile::parse(&data).unwrap();
+        let at = file.table(b"OS/2").unwrap().as_ptr() as usize - data.as_ptr() as usize;
+        data[at + 64..at + 68].copy_from_slice(&[0x25, 0x00, 0x25, 0x4F]);
+
+        let (patched, report) = FontPatcher::new(&font)
+            .code_points((0x2500..=0x259F).chain(0x2800..=0x28FF))
+            .unwrap()
+            .patch(&data)
+            .unwrap();
+        assert_eq!(report.added, (0x2800..=0x28FF).collect::<Vec<_>>());
+        let os2 = FontFile::parse(&patched).unwrap().table(b"OS/2").unwrap();
+        assert_eq!(os2[64..68], [0x25, 0x00, 0x28, 0xFF]);
+        let face = ttf_parser::Face::parse(&patched, 0).unwrap();
+        let ranges = face.tables().os2.unwrap().unicode_ranges();
+        assert!(ranges.contains_char('─') && ranges.contains_char('⠿'));
+        assert!(!ranges.contains_char('■'));
+    }
+
+    #[test]
+    fn keeps_or_replaces_existing_glyphs() {
+        let font = Font::new(Metrics::<f64>::default());
+        let data = TrueTypeWriter::new(&font, "Box Drawing").write();
+        let (patched, report) = FontPatcher::new(&font).patch(&data).unwrap();
+        assert_eq!(report.kept.len(), 0xA0);
+        assert!(report.added.is_empty());
+        assert_eq!(
+            super::super::checksum(&patched),
+            super::super::CHECKSUM_MAGIC
+        );
+
+        let (patched, report) = FontPatcher::new(&font)
+            .policy(MergePolicy::Replace)
+            .patch(&patched)
+            .unwrap();
+        assert_eq!(report.replaced.len(), 0xA0);
+        let face = ttf_parser::Face::parse(&patched, 0).unwrap();
+        let bbox = face
+            .glyph_bounding_box(face.glyph_index('█').unwrap())
+            .unwrap();
+        assert_eq!((bbox.x_min, bbox.y_max), (0, 1000));
+    }
+
+    #[test]
+    fn refuses_cff() {
+        let font = Font::new(Metrics::<f64>::default());
+        let mut data = TrueTypeWriter::new(&font, "Box Drawing").write();
+        data[..4].copy_from_slice(b"OTTO");
+        // rename `glyf` to `CFF `
+        let at = data.windows(4).position(|w| w == b"glyf").unwrap();
+        data[at..at + 4].copy_from_slice(b"CFF ");
+        assert_eq!(
+            FontPatcher::new(&font).patch(&data).err(),
+            Some(SfntError::Unsupported("fonts with CFF outlines"))
+        );
+    }
+}
@@ -0,0 +1,258 @@
+use std::collections::{BTreeMap, HashMap};
+
+use num::Float;
+
+use super::SfntError;
+use crate::drawing_command::Metrics;
+
+/// `OS/2.fsSelection` bit telling to use the typographic line metrics.
+const USE_TYPO_METRICS: u16 = 1 << 7;
+
+/// Big-endian reads from a table, failing past its end.
+#[derive(Clone, Copy)]
+pub(crate) struct Bytes<'a>(pub &'a [u8]);
+
+impl<'a> Bytes<'a> {
+    pub fn slice(&self, offset: usize, len: usize) -> Result<&'a [u8], SfntError> {
+        offset
+            .checked_add(len)
+            .and_then(|end| self.0.get(offset..end))
+            .ok_or(SfntError::Truncated)
+    }
+
+    pub fn u16(&self, offset: usize) -> Result<u16, SfntError> {
+        let b = self.slice(offset, 2)?;
+        Ok(u16::from_be_bytes([b[0], b[1]]))
+    }
+
+    pub fn i16(&self, offset: usize) -> Result<i16, SfntError> {
+        self.u16(offset).map(|v| v as i16)
+    }
+
+    pub fn u32(&self, offset: usize) -> Result<u32, SfntError> {
+        let b = self.slice(offset, 4)?;
+        Ok(u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+    }
+}
+
+/// A TrueType or OpenType font file, with its tables located.
+pub struct FontFile<'a> {
+    data: &'a [u8],
+    tables: Vec<([u8; 4], &'a [u8])>,
+}
+
+impl<'a> FontFile<'a> {
+    pub fn parse(data: &'a [u8]) -> Result<FontFile<'a>, SfntError> {
+        let bytes = Bytes(data);
+        match bytes.slice(0, 4)? {
+            [0, 1, 0, 0] | b"true" | b"OTTO" => {}
+            b"ttcf" => return Err(SfntError::Unsupported("font collections")),
+            _ => return Err(SfntError::UnknownFormat),
+        }
+        let count = bytes.u16(4)? as usize;
+        let mut tables = Vec::with_capacity(count);
+        for i in 0..count {
+            let record = 12 + 16 * i;
+            let mut tag = [0; 4];
+            tag.copy_from_slice(bytes.slice(record, 4)?);
+            let offset = bytes.u32(record + 8)? as usize;
+            let length = bytes.u32(record + 12)? as usize;
+            tables.push((tag, bytes.slice(offset, length)?));
+        }
+        Ok(FontFile { data, tables })
+    }
+
+    /// The whole file.
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// Tags of all tables, in directory order.
+    pub fn tags(&self) -> impl Iterator<Item = &[u8; 4]> {
+        self.tables.iter().map(|(tag, _)| tag)
+    }
+
+    pub fn table(&self, tag: &[u8; 4]) -> Option<&'a [u8]> {
+        self.tables
+            .iter()
+            .find(|(t, _)| t == tag)
+            .map(|(_, data)| *data)
+    }
+
+    pub(crate) fn require(&self, tag: &[u8; 4]) -> Result<Bytes<'a>, SfntError> {
+        self.table(tag)
+            .map(Bytes)
+            .ok_or(SfntError::MissingTable(*tag))
+    }
+
+    pub fn units_per_em(&self) -> Result<u16, SfntError> {
+        self.require(b"head")?.u16(18)
+    }
+
+    pub fn num_glyphs(&self) -> Result<u16, SfntError> {
+        self.require(b"maxp")?.u16(4)
+    }
+
+    /// Advance width of every glyph.
+    pub fn advance_widths(&self) -> Result<Vec<u16>, SfntError> {
+        let num_metrics = self.require(b"hhea")?.u16(34)? as usize;
+        let hmtx = self.require(b"hmtx")?;
+        let num_glyphs = self.num_glyphs()? as usize;
+        let mut widths = Vec::with_capacity(num_glyphs);
+        for i in 0..num_metrics.min(num_glyphs) {
+            widths.push(hmtx.u16(4 * i)?);
+        }
+        // the remaining glyphs repeat the last advance
+        let last = widths.last().copied().unwrap_or(0);
+        widths.resize(num_glyphs, last);
+        Ok(widths)
+    }
+
+    /// Whether the outlines are in a `CFF` or `CFF2` table rather than `glyf`.
+    pub fn has_cff(&self) -> bool {
+        self.table(b"CFF ").is_some() || self.table(b"CFF2").is_some()
+    }
+
+    /// The glyph of every code point, from the Unicode `cmap` subtable of
+    /// format 12 or else format 4.
+    pub fn char_map(&self) -> Result<BTreeMap<u32, u16>, SfntError> {
+        let cmap = self.require(b"cmap")?;
+        let mut best = None;
+        for i in 0..cmap.u16(2)? as usize {
+            let (platform, encoding) = (cmap.u16(4 + 8 * i)?, cmap.u16(6 + 8 * i)?);
+            let offset = cmap.u32(8 + 8 * i)? as usize;
+            let format = cmap.u16(offset)?;
+            let unicode = platform == 0 || (platform == 3 && (encoding == 1 || encoding == 10));
+            if unicode && (format == 12 || (format == 4 && best.is_none())) {
+                best = Some((format, offset));
+            }
+        }
+
+        let mut map = BTreeMap::new();
+        match best {
+            Some((12, offset)) => {
+                let groups = cmap.u32(offset + 12)? as usize;
+                for i in 0..groups {
+                    let group = offset + 16 + 12 * i;
+                    let (start, end) = (cmap.u32(group)?, cmap.u32(group + 4)?);
+                    let gid = cmap.u32(group + 8)?;
+                    for c in start..=end.min(0x10FFFF) {
+                        map.insert(c, (gid + c - start) as u16);
+                    }
+                }
+            }
+            Some((_, offset)) => {
+                let seg_count = cmap.u16(offset + 6)? as usize / 2;
+                let ends = offset + 14;
+                let starts = ends + 2 * seg_count + 2;
+                let deltas = starts + 2 * seg_count;
+                let range_offsets = deltas + 2 * seg_count;
+                for i in 0..seg_count {
+                    let (start, end) = (cmap.u16(starts + 2 * i)?, cmap.u16(ends + 2 * i)?);
+                    let delta = cmap.u16(deltas + 2 * i)?;
+                    let range_offset = cmap.u16(range_offsets + 2 * i)? as usize;
+                    for c in start..=end.min(0xFFFE) {
+                        let gid = if range_offset == 0 {
+                            c.wrapping_add(delta)
+                        } else {
+                            let at =
+                                range_offsets + 2 * i + range_offset + 2 * (c - start) as usize;
+                            match cmap.u16(at)? {
+                                0 => 0,
+                                gid => gid.wrapping_add(delta),
+                            }
+                        };
+                        if gid != 0 {
+                            map.insert(c as u32, gid);
+                        }
+                    }
+                }
+            }
+            None => return Err(SfntError::Unsupported("fonts without a Unicode cmap")),
+        }
+        Ok(map)
+    }
+
+    /// Metrics for box drawing glyphs matching the font.
+    ///
+    /// The glyph width is the most common advance width, and the block area
+    /// spans the line, from the hhea descender to the hhea ascender, or the
+    /// typographic ones if the font asks for them. The median is put at 5/8
+    /// of the x-height, or else 5/11 of the cap height, which both match the
+    /// default metrics for Source Code Pro. The stroke weight follows the
+    /// underline thickness. Values the font doesn't have keep their default
+    /// proportions.
+    pub fn metrics<F: Float>(&self) -> Result<Metrics<F>, SfntError> {
+        let mut counts: HashMap<u16, usize> = HashMap::new();
+        for width in self.advance_widths()?.into_iter().filter(|w| *w > 0) {
+            *counts.entry(width).or_default() += 1;
+        }
+        let width = counts
+            .into_iter()
+            .max_by_key(|(width, count)| (*count, std::cmp::Reverse(*width)))
+            .map(|(width, _)| width)
+            .ok_or(SfntError::Unsupported("fonts without advance widths"))?;
+
+        let hhea = self.require(b"hhea")?;
+        let (mut ascender, mut descender) = (hhea.i16(4)?, hhea.i16(6)?);
+        let os2 = self.table(b"OS/2").map(Bytes);
+        let mut median = None;
+        if let Some(os2) = os2 {
+            if os2.u16(62)? & USE_TYPO_METRICS != 0 {
+                ascender = os2.i16(68)?;
+                descender = os2.i16(70)?;
+            }
+            if os2.u16(0)? >= 2 {
+                let (x_height, cap_height) = (os2.i16(86)? as f64, os2.i16(88)? as f64);
+                if x_height > 0. {
+                    median = Some(x_height * 5. / 8.);
+                } else if cap_height > 0. {
+                    median = Some(cap_height * 5. / 11.);
+                }
+            }
+        }
+        let thickness = match self.table(b"post") {
+            Some(post) => Bytes(post).i16(10)?,
+            None => 0,
+        };
+
+        let f = |v: f64| F::from(v).unwrap();
+        let mut metrics =
+            Metrics::for_cell(f(width as f64), f(ascender as f64), f(descender as f64));
+        if let Some(median) = median {
+            metrics = metrics.with_median(f(median));
+        }
+        if thickness > 0 {
+            metrics = metrics.with_stroke(f(thickness as f64));
+        }
+        Ok(metrics)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::font::Font;
+    use crate::sfnt::TrueTypeWriter;
+
+    #[test]
+    fn metrics_round_trip() {
+        let font = Font::new(Metrics::<f64>::default());
+        let data = TrueTypeWriter::new(&font, "Box Drawing").write();
+        let file = FontFile::parse(&data).unwrap();
+        assert_eq!(file.units_per_em(), Ok(1000));
+        assert_eq!(file.metrics::<f64>(), Ok(Metrics::default()));
+    }
+
+    #[test]
+    fn rejects_other_files() {
+        assert_eq!(
+            FontFile::parse(b"wOFF").err(),
+            Some(SfntError::UnknownFormat)
+        );
+        assert_eq!(
+            FontFile::parse(&[0, 1, 0, 0, 0]).err(),
+            Some(SfntError::Truncated)
+        );
+    }
+}
@@ -0,0 +1,508 @@
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::ops::{AddAssign, RangeInclusive, SubAssign};
+
+use num::Float;
+
+use super::glyf::Glyph;
+use super::{assemble, search_params, Put, SfntError, HEAD_MAGIC};
+use crate::font::{CodePointError, Font};
+
+/// Writes the glyphs of a [`Font`] into a standalone TrueType font file.
+///
+/// The font holds a `.notdef` glyph and the glyphs of the chosen code
+/// points, by default [`Font::default_code_points`], with overlaps removed,
+/// quadratic curves and TrueType contour direction. Each glyph advances by
+/// `Metrics.width`, and the block area of the metrics spans the line
+/// height, so blocks of adjacent lines touch.
+pub struct TrueTypeWriter<'a, F: Float> {
+    font: &'a Font<F>,
+    family_name: String,
+    units_per_em: u16,
+    code_points: Vec<u32>,
+}
+
+impl<'a, F> TrueTypeWriter<'a, F>
+where
+    F: Float + Default + AddAssign + SubAssign + 'static,
+{
+    /// A writer for a font of the given family name and 1000 units per em.
+    pub fn new(font: &'a Font<F>, family_name: &str) -> TrueTypeWriter<'a, F> {
+        TrueTypeWriter {
+            font,
+            family_name: family_name.to_string(),
+            units_per_em: 1000,
+            code_points: font.default_code_points().collect(),
+        }
+    }
+
+    /// The code points to include, all with a glyph in the font.
+    pub fn code_points(
+        mut self,
+        code_points: impl IntoIterator<Item = u32>,
+    ) -> Result<Self, CodePointError> {
+        self.code_points = self.font.select(code_points)?;
+        Ok(self)
+    }
+
+    /// Sets how many font units the em square holds, without scaling the
+    /// glyphs.
+    pub fn units_per_em(mut self, units_per_em: u16) -> Self {
+        self.units_per_em = units_per_em;
+        self
+    }
+
+    pub fn write(&self) -> Vec<u8> {
+        let metrics = self.font.metrics();
+        let advance = units(metrics.width) as u16;
+        let ascender = units(metrics.block_top.y);
+        let descender = units(metrics.block_origin.y);
+
+        let code_points = &self.code_points;
+        let mut glyphs = vec![Glyph::default()];
+        let mut names: Vec<Cow<str>> = vec![".notdef".into()];
+        for c in code_points.iter() {
+            glyphs.push(truetype_glyph(self.font, *c));
+            names.push(Font::<F>::glyph_name(*c));
+        }
+        let mapping: BTreeMap<u32, u16> = code_points
+            .iter()
+            .enumerate()
+            .map(|(i, c)| (*c, i as u16 + 1))
+            .collect();
+        let bounds: Vec<_> = glyphs.iter().map(Glyph::bounds).collect();
+        let inked = || bounds.iter().flatten();
+        let num_glyphs = glyphs.len() as u16;
+
+        // glyf and loca
+        let (mut glyf, mut loca) = (Vec::new(), Vec::new());
+        for glyph in glyphs.iter() {
+            loca.put_u32(glyf.len() as u32);
+            glyf.extend(glyph.encode());
+            glyf.resize((glyf.len() + 3) & !3, 0);
+        }
+        loca.put_u32(glyf.len() as u32);
+
+        let mut hmtx = Vec::new();
+        for bounds in bounds.iter() {
+            hmtx.put_u16(advance);
+            hmtx.put_i16(bounds.map_or(0, |b| b.0));
+        }
+
+        let mut head = Vec::new();
+        head.put_u32(0x0001_0000);
+        // fontRevision 1.0
+        head.put_u32(0x0001_0000);
+        // checkSumAdjustment, filled in by `assemble`
+        head.put_u32(0);
+        head.put_u32(HEAD_MAGIC);
+        // baseline at y=0, left sidebearing point at x=0, integer ppem
+        head.put_u16(0x000B);
+        head.put_u16(self.units_per_em);
+        // created and modified, left at the epoch so output is reproducible
+        head.put_i64(0);
+        head.put_i64(0);
+        head.put_i16(inked().map(|b| b.0).min().unwrap_or(0));
+        head.put_i16(inked().map(|b| b.1).min().unwrap_or(0));
+        head.put_i16(inked().map(|b| b.2).max().unwrap_or(0));
+        head.put_i16(inked().map(|b| b.3).max().unwrap_or(0));
+        // macStyle, lowestRecPPEM, fontDirectionHint
+        head.put_u16(0);
+        head.put_u16(8);
+        head.put_i16(2);
+        // long loca offsets, glyphDataFormat
+        head.put_i16(1);
+        head.put_i16(0);
+
+        let mut hhea = Vec::new();
+        hhea.put_u32(0x0001_0000);
+        hhea.put_i16(ascender);
+        hhea.put_i16(descender);
+        // lineGap
+        hhea.put_i16(0);
+        hhea.put_u16(advance);
+        hhea.put_i16(inked().map(|b| b.0).min().unwrap_or(0));
+        hhea.put_i16(inked().map(|b| advance as i16 - b.2).min().unwrap_or(0));
+        hhea.put_i16(inked().map(|b| b.2).max().unwrap_or(0));
+        // caretSlopeRise, caretSlopeRun, caretOffset, reserved
+        hhea.put_i16(1);
+        hhea.put_i16(0);
+        hhea.put_i16(0);
+        hhea.extend_from_slice(&[0; 8]);
+        // metricDataFormat
+        hhea.put_i16(0);
+        hhea.put_u16(num_glyphs);
+
+        let mut maxp = Vec::new();
+        maxp.put_u32(0x0001_0000);
+        maxp.put_u16(num_glyphs);
+        maxp.put_u16(glyphs.iter().map(Glyph::num_points).max().unwrap_or(0) as u16);
+        maxp.put_u16(glyphs.iter().map(|g| g.contours.len()).max().unwrap_or(0) as u16);
+        // maxCompositePoints, maxCompositeContours
+        maxp.put_u16(0);
+        maxp.put_u16(0);
+        // maxZones
+        maxp.put_u16(2);
+        // no hinting: twilight points, storage, function and instruction
+        // definitions, stack elements, instructions, components and depth
+        maxp.extend_from_slice(&[0; 18]);
+
+        let tables = [
+            (*b"OS/2", self.os2(code_points)),
+            // a few hundred segments at most, far from the limit
+            (*b"cmap", cmap(&mapping, None).unwrap()),
+            (*b"glyf", glyf),
+            (*b"head", head),
+            (*b"hhea", hhea),
+            (*b"hmtx", hmtx),
+            (*b"loca", loca),
+            (*b"maxp", maxp),
+            (*b"name", self.name()),
+            (*b"post", self.post(&names)),
+        ];
+        assemble(&tables)
+    }
+
+    fn os2(&self, code_points: &[u32]) -> Vec<u8> {
+        let metrics = self.font.metrics();
+        let em = self.units_per_em as i32;
+        let scaled = |permille: i32| (em * permille / 1000) as i16;
+        let ascender = units(metrics.block_top.y);
+        let descender = units(metrics.block_origin.y);
+
+        let mut os2 = Vec::new();
+        os2.put_u16(4);
+        // xAvgCharWidth, usWeightClass, usWidthClass, fsType (installable)
+        os2.put_i16(units(metrics.width));
+        os2.put_u16(400);
+        os2.put_u16(5);
+        os2.put_u16(0);
+        // subscript and superscript size and offset
+        for (x_size, y_size, y_offset) in [(650, 600, 75), (650, 600, 350)] {
+            os2.put_i16(scaled(x_size));
+            os2.put_i16(scaled(y_size));
+            os2.put_i16(0);
+            os2.put_i16(scaled(y_offset));
+        }
+        // strikeout size and position
+        os2.put_i16(units(metrics.stroke));
+        os2.put_i16(units(metrics.median));
+        // sFamilyClass
+        os2.put_i16(0);
+        // PANOSE: Latin text, monospaced
+        os2.extend_from_slice(&[2, 0, 0, 9, 0, 0, 0, 0, 0, 0]);
+        // ulUnicodeRange
+        for range in unicode_ranges(code_points) {
+            os2.put_u32(range);
+        }
+        os2.extend_from_slice(b"NONE");
+        // fsSelection: REGULAR, USE_TYPO_METRICS
+        os2.put_u16(0x00C0);
+        let first = code_points.iter().copied().min().unwrap_or(0);
+        let last = code_points.iter().copied().max().unwrap_or(0);
+        os2.put_u16(first.min(0xFFFF) as u16);
+        os2.put_u16(last.min(0xFFFF) as u16);
+        os2.put_i16(ascender);
+        os2.put_i16(descender);
+        os2.put_i16(0);
+        os2.put_u16(ascender.max(0) as u16);
+        os2.put_u16((-descender).max(0) as u16);
+        // ulCodePageRange: IBM PC US (bit 63)
+        os2.put_u32(0);
+        os2.put_u32(1 << 31);
+        // sxHeight, sCapHeight: there are no letters
+        os2.put_i16(0);
+        os2.put_i16(0);
+        // usDefaultChar, usBreakChar, usMaxContext
+        os2.put_u16(0);
+        os2.put_u16(0x20);
+        os2.put_u16(0);
+        os2
+    }
+
+    fn name(&self) -> Vec<u8> {
+        let postscript: String = self
+            .family_name
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric())
+            .collect();
+        let postscript = format!("{}-Regular", postscript);
+        let version = "Version 1.000";
+        let records = [
+            (1, self.family_name.clone()),
+            (2, String::from("Regular")),
+            (3, format!("1.000;NONE;{}", postscript)),
+            (4, format!("{} Regular", self.family_name)),
+            (5, String::from(version)),
+            (6, postscript),
+        ];
+
+        let mut name = Vec::new();
+        let mut strings = Vec::new();
+        name.put_u16(0);
+        name.put_u16(records.len() as u16);
+        name.put_u16(6 + 12 * records.len() as u16);
+        for (id, text) in records.iter() {
+            let encoded: Vec<u8> = text.encode_utf16().flat_map(u16::to_be_bytes).collect();
+            // Windows, Unicode BMP, English (United States)
+            name.put_u16(3);
+            name.put_u16(1);
+            name.put_u16(0x0409);
+            name.put_u16(*id);
+            name.put_u16(encoded.len() as u16);
+            name.put_u16(strings.len() as u16);
+            strings.extend(encoded);
+        }
+        name.extend(strings);
+        name
+    }
+
+    fn post(&self, names: &[Cow<str>]) -> Vec<u8> {
+        let metrics = self.font.metrics();
+        let mut post = Vec::new();
+        post.put_u32(0x0002_0000);
+        // italicAngle
+        post.put_u32(0);
+        post.put_i16(units(metrics.block_origin.y + metrics.stroke));
+        post.put_i16(units(metrics.stroke));
+        // isFixedPitch, then the memory usage hints
+        post.put_u32(1);
+        post.extend_from_slice(&[0; 16]);
+
+        post.put_u16(names.len() as u16);
+        let mut strings = Vec::new();
+        for (i, name) in names.iter().enumerate() {
+            if i == 0 {
+                // `.notdef` is the first of the standard Macintosh names
+                post.put_u16(0);
+            } else {
+                post.put_u16(257 + i as u16);
+                strings.put_u8(name.len() as u8);
+                strings.extend_from_slice(name.as_bytes());
+            }
+        }
+        post.extend(strings);
+        post
+    }
+}
+
+/// A glyph drawn by `font`, with overlaps removed, quadratic curves and
+/// TrueType contour direction.
+pub(crate) fn truetype_glyph<F>(font: &Font<F>, c: u32) -> Glyph
+where
+    F: Float + Default + AddAssign + SubAssign + 'static,
+{
+    let outline = font.outline(c).remove_overlap();
+    Glyph::from_outline(&outline.correct_direction(true))
+}
+
+/// The four words of `OS/2.ulUnicodeRange` for the blocks `code_points`
+/// draw from: Miscellaneous Technical (bit 39), Box Drawing (bit 43), Block
+/// Elements (bit 44), Geometric Shapes (bit 45), Non-Plane 0 (bit 57) for
+/// the Symbols for Legacy Computing, Private Use Area (bit 60) and Braille
+/// Patterns (bit 82).
+pub(crate) fn unicode_ranges(code_points: &[u32]) -> [u32; 4] {
+    let any = |range: RangeInclusive<u32>| code_points.iter().any(|c| range.contains(c)) as u32;
+    [
+        0,
+        any(0x2300..=0x23FF) << 7
+            | any(0x2500..=0x257F) << 11
+            | any(0x2580..=0x259F) << 12
+            | any(0x25A0..=0x25FF) << 13
+            | any(0x10000..=0x10FFFF) << 25
+            | any(0xE000..=0xF8FF) << 28,
+        any(0x2800..=0x28FF) << 18,
+        0,
+    ]
+}
+
+/// A `cmap` table with a format 4 subtable for the BMP and a format 12
+/// subtable covering all code points, plus the given format 14 subtable
+/// of variation sequences.
+///
+/// Fails if the BMP needs more segments than a format 4 subtable holds.
+pub(crate) fn cmap(
+    mapping: &BTreeMap<u32, u16>,
+    variations: Option<&[u8]>,
+) -> Result<Vec<u8>, SfntError> {
+    // runs of consecutive code points mapped to consecutive glyphs
+    let mut runs: Vec<(u32, u32, u16)> = Vec::new();
+    for (c, gid) in mapping.iter() {
+        if let Some((start, end, start_gid)) = runs.last_mut() {
+            if *end + 1 == *c && *start_gid as u32 + (c - *start) == *gid as u32 {
+                *end = *c;
+                continue;
+            }
+        }
+        runs.push((*c, *c, *gid));
+    }
+
+    let mut segments: Vec<(u16, u16, u16)> = runs
+        .iter()
+        .filter(|(start, _, _)| *start <= 0xFFFF)
+        .map(|(start, end, gid)| (*start as u16, (*end).min(0xFFFE) as u16, *gid))
+        .collect();
+    segments.push((0xFFFF, 0xFFFF, 0));
+    let seg_count = segments.len() as u32;
+    let length = 16 + 8 * seg_count;
+    if length > 0xFFFF {
+        return Err(SfntError::Unsupported(
+            "cmap format 4 subtables over 64 KiB",
+        ));
+    }
+    let (search_range, entry_selector, range_shift) = search_params(seg_count, 2);
+
+    let mut format4 = Vec::new();
+    format4.put_u16(4);
+    format4.put_u16(length as u16);
+    // language
+    format4.put_u16(0);
+    format4.put_u16((seg_count * 2) as u16);
+    format4.put_u16(search_range);
+    format4.put_u16(entry_selector);
+    format4.put_u16(range_shift);
+    for (_, end, _) in segments.iter() {
+        format4.put_u16(*end);
+    }
+    // reservedPad
+    format4.put_u16(0);
+    for (start, _, _) in segments.iter() {
+        format4.put_u16(*start);
+    }
+    for (start, _, gid) in segments.iter() {
+        // the final segment maps 0xFFFF to glyph 0
+        let delta = if *start == 0xFFFF {
+            1
+        } else {
+            gid.wrapping_sub(*start)
+        };
+        format4.put_u16(delta);
+    }
+    for _ in segments.iter() {
+        format4.put_u16(0);
+    }
+
+    let mut format12 = Vec::new();
+    format12.put_u16(12);
+    format12.put_u16(0);
+    format12.put_u32(16 + 12 * runs.len() as u32);
+    // language
+    format12.put_u32(0);
+    format12.put_u32(runs.len() as u32);
+    for (start, end, gid) in runs.iter() {
+        format12.put_u32(*start);
+        format12.put_u32(*end);
+        format12.put_u32(*gid as u32);
+    }
+
+    let mut subtables = vec![format4, format12];
+    // Unicode BMP, Windows Unicode BMP, Windows Unicode full repertoire,
+    // with the index of their subtable
+    let mut records = vec![(0, 3, 0), (3, 1, 0), (3, 10, 1)];
+    if let Some(variations) = variations {
+        subtables.push(variations.to_vec());
+        records.insert(1, (0, 5, 2));
+    }
+
+    let mut cmap = Vec::new();
+    cmap.put_u16(0);
+    cmap.put_u16(records.len() as u16);
+    let mut offsets = Vec::new();
+    let mut offset = 4 + 8 * records.len() as u32;
+    for subtable in subtables.iter() {
+        offsets.push(offset);
+        offset += subtable.len() as u32;
+    }
+    for (platform, encoding, subtable) in records {
+        cmap.put_u16(platform);
+        cmap.put_u16(encoding);
+        cmap.put_u32(offsets[subtable]);
+    }
+    for subtable in subtables {
+        cmap.extend(subtable);
+    }
+    Ok(cmap)
+}
+
+fn units<F: Float>(v: F) -> i16 {
+    v.round().to_i16().unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::drawing_command::Metrics;
+
+    #[test]
+    fn parses_back() {
+        let font = Font::new(Metrics::<f64>::default());
+        let data = TrueTypeWriter::new(&font, "Box Drawing").write();
+        let face = ttf_parser::Face::parse(&data, 0).unwrap();
+
+        // the box drawing and block elements only, by default
+        assert_eq!(face.number_of_glyphs(), 0xA0 + 1);
+        assert_eq!(face.units_per_em(), 1000);
+        assert_eq!((face.ascender(), face.descender()), (1000, -400));
+        assert!(face.is_monospaced());
+        let names: Vec<String> = face
+            .names()
+            .into_iter()
+            .filter_map(|name| name.to_string())
+            .collect();
+        assert!(names.contains(&String::from("Box Drawing")));
+        let ranges = face.tables().os2.unwrap().unicode_ranges();
+        assert!(ranges.contains_char('─') && ranges.contains_char('█'));
+        assert!(!ranges.contains_char('⠿') && !ranges.contains_char('■'));
+        let full_block = face.glyph_index('█').unwrap();
+        let bbox = face.glyph_bounding_box(full_block).unwrap();
+        assert_eq!(
+            (bbox.x_min, bbox.y_min, bbox.x_max, bbox.y_max),
+            (0, -400, 600, 1000)
+        );
+
+        let data = TrueTypeWriter::new(&font, "Box Drawing")
+            .code_points(font.code_points())
+            .unwrap()
+            .write();
+        let face = ttf_parser::Face::parse(&data, 0).unwrap();
+        assert_eq!(
+            face.number_of_glyphs() as usize,
+            font.code_points().count() + 1
+        );
+        for c in font.code_points() {
+            let glyph = face.glyph_index(char::from_u32(c).unwrap()).unwrap();
+            assert_eq!(face.glyph_hor_advance(glyph), Some(600));
+            assert_eq!(face.glyph_name(glyph), Some(&*Font::<f64>::glyph_name(c)));
+        }
+        let ranges = face.tables().os2.unwrap().unicode_ranges();
+        for c in ['─', '█', '⠿', '⌠', '■'] {
+            assert!(ranges.contains_char(c));
+        }
+
+        let writer = TrueTypeWriter::new(&font, "Box Drawing");
+        let error = writer.code_points([0x2500, 0xE0B0]).err();
+        assert_eq!(error, Some(CodePointError::NoGlyph(0xE0B0)));
+    }
+
+    #[test]
+    fn cmap_segments_fit_format_4() {
+        // every other code point, so each one is a segment of its own, and
+        // the final segment for 0xFFFF
+        let mapping = |count: u32| (0..count).map(|i| (2 * i, i as u16)).collect();
+        let table = cmap(&mapping(8188), None).unwrap();
+        assert_eq!(table[4 + 8 * 3 + 2..][..2], 65528u16.to_be_bytes());
+        assert_eq!(
+            cmap(&mapping(8189), None).err(),
+            Some(SfntError::Unsupported(
+                "cmap format 4 subtables over 64 KiB"
+            ))
+        );
+    }
+
+    #[test]
+    fn checksums_add_up() {
+        let font = Font::new(Metrics::<f64>::default());
+        let data = TrueTypeWriter::new(&font, "Box Drawing").write();
+        assert_eq!(super::super::checksum(&data), super::super::CHECKSUM_MAGIC);
+    }
+}
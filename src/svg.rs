@@ -0,0 +1,112 @@
+use std::cell::RefCell;
+use std::fmt::Write;
+use std::marker::PhantomData;
+
+use num::Float;
+
+use crate::drawing_command::{Canvas, Metrics, Point};
+
+/// A canvas that records the drawing as SVG path data.
+///
+/// Coordinates are written as they are received, i.e. in font units with the
+/// y axis pointing up.
+pub struct SvgCanvas<F: Float> {
+    data: RefCell<String>,
+    _float: PhantomData<F>,
+}
+
+impl<F: Float> Default for SvgCanvas<F> {
+    fn default() -> Self {
+        SvgCanvas {
+            data: RefCell::new(String::new()),
+            _float: PhantomData,
+        }
+    }
+}
+
+impl<F: Float> SvgCanvas<F> {
+    pub fn new() -> SvgCanvas<F> {
+        SvgCanvas::default()
+    }
+
+    /// The path data recorded so far, suitable for the `d` attribute.
+    pub fn path_data(&self) -> String {
+        self.data.borrow().clone()
+    }
+
+    pub fn into_path_data(self) -> String {
+        self.data.into_inner()
+    }
+
+    fn push(&self, command: char, points: &[&Point<F>]) {
+        let mut data = self.data.borrow_mut();
+        if !data.is_empty() {
+            data.push(' ');
+        }
+        data.push(command);
+        for pt in points {
+            write!(data, " {} {}", number(pt.x), number(pt.y)).unwrap();
+        }
+    }
+}
+
+impl<F: Float> Canvas<F> for SvgCanvas<F> {
+    fn move_to(&self, pt: &Point<F>) {
+        self.push('M', &[pt]);
+    }
+
+    fn line_to(&self, pt: &Point<F>) {
+        self.push('L', &[pt]);
+    }
+
+    fn curve_to(&self, pt1: &Point<F>, pt2: &Point<F>, ptend: &Point<F>) {
+        self.push('C', &[pt1, pt2, ptend]);
+    }
+
+    fn close_path(&self) {
+        self.push('Z', &[]);
+    }
+}
+
+/// Wraps path data into a standalone SVG document covering the block area.
+///
+/// The path is mirrored vertically, so the glyph shows upright even though
+/// font units grow upwards and SVG units grow downwards.
+pub(crate) fn document<F: Float>(metrics: &Metrics<F>, path_data: &str) -> String {
+    let height = metrics.block_top.y - metrics.block_origin.y;
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"{} {} {} {}\">\
+         <path transform=\"scale(1 -1)\" d=\"{}\"/></svg>",
+        number(metrics.block_origin.x),
+        number(-metrics.block_top.y),
+        number(metrics.width),
+        number(height),
+        path_data,
+    )
+}
+
+/// Formats a coordinate with at most three decimals and no trailing zeros.
+pub(crate) fn number<F: Float>(v: F) -> String {
+    let v = (v.to_f64().unwrap() * 1000.).round() / 1000.;
+    // avoid printing "-0"
+    if v == 0. {
+        return String::from("0");
+    }
+    format!("{}", v)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::font::Font;
+
+    #[test]
+    fn full_block() {
+        let font = Font::new(Metrics::<f64>::default());
+        assert_eq!(
+            font.to_svg(0x2588),
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 -1000 600 1400\">\
+             <path transform=\"scale(1 -1)\" d=\"M 0 -400 L 600 -400 L 600 1000 L 0 1000 Z\"/></svg>"
+        );
+    }
+}
@@ -0,0 +1,214 @@
+//! Recipes for pieces of Miscellaneous Technical: the parts of tall
+//! brackets and integrals, U+2320, U+2321 and U+239B to U+23AE, and the
+//! horizontal scan lines, U+23BA to U+23BD.
+//!
+//! Stems run over the line height with its overlap, like [`vert_bar`], and
+//! scan lines are light horizontal bars, so stacked pieces join up.
+//!
+//! [`vert_bar`]: crate::drawing_command::DrawingCommand::vert_bar
+
+use std::ops::{AddAssign, SubAssign};
+
+use num::Float;
+
+use crate::commands::{ArcBuilder, HorBarBuilder, HorLineBuilder, VertBarBuilder, VertLineBuilder};
+use crate::drawing_command::{Metrics, Point, Side};
+use crate::font::{Configure, Recipe};
+
+/// How far hooks and serifs reach from the stem, in eighths of the width.
+const REACH: u32 = 3;
+
+/// The glyph names of Adobe Symbol, from U+239B.
+const NAMES: [&str; 20] = [
+    "parenlefttp",
+    "parenleftex",
+    "parenleftbt",
+    "parenrighttp",
+    "parenrightex",
+    "parenrightbt",
+    "bracketlefttp",
+    "bracketleftex",
+    "bracketleftbt",
+    "bracketrighttp",
+    "bracketrightex",
+    "bracketrightbt",
+    "bracelefttp",
+    "braceleftmid",
+    "braceleftbt",
+    "braceex",
+    "bracerighttp",
+    "bracerightmid",
+    "bracerightbt",
+    "integralex",
+];
+
+fn stem<F>() -> Configure<F>
+where
+    F: Float + AddAssign + SubAssign + 'static,
+{
+    Box::new(|_: &Metrics<F>| VertBarBuilder::default().build().unwrap().into())
+}
+
+/// Where the arc of `side`, as in U+256D to U+2570, leaves the stem, and
+/// where it ends: `reach` across, at height `y`.
+fn arc<F>(side: Side, y: fn(&Metrics<F>) -> F) -> Configure<F>
+where
+    F: Float + AddAssign + SubAssign + 'static,
+{
+    Box::new(move |m: &Metrics<F>| {
+        let two = F::one() + F::one();
+        let reach = m.width * F::from(REACH).unwrap() / F::from(8).unwrap();
+        let (start, x) = match side {
+            Side::TopLeft => (m.median - m.height / two, m.width / two + reach),
+            Side::TopRight => (m.median - m.height / two, m.width / two - reach),
+            Side::BottomLeft => (m.median + m.height / two, m.width / two + reach),
+            Side::BottomRight => (m.median + m.height / two, m.width / two - reach),
+        };
+        let mut builder = ArcBuilder::default();
+        builder
+            .start(Point::new(m.width / two, start))
+            .end(Point::new(x, y(m)))
+            .side(side)
+            .stroke(m.stroke)
+            .radius(reach)
+            .butt(F::zero());
+        builder.build().unwrap().into()
+    })
+}
+
+/// Just below the top of the block area.
+fn top<F: Float>(m: &Metrics<F>) -> F {
+    m.block_top.y - m.stroke / (F::one() + F::one())
+}
+
+/// Just above the bottom of the block area.
+fn bottom<F: Float>(m: &Metrics<F>) -> F {
+    m.block_origin.y + m.stroke / (F::one() + F::one())
+}
+
+fn median<F: Float>(m: &Metrics<F>) -> F {
+    m.median
+}
+
+/// A square bracket corner: the stem from the other end up or down to
+/// the serif, which goes right or left.
+fn corner<F>(upper: bool, right: bool) -> Vec<Configure<F>>
+where
+    F: Float + AddAssign + SubAssign + 'static,
+{
+    let stem: Configure<F> = Box::new(move |m: &Metrics<F>| {
+        let two = F::one() + F::one();
+        let (from, to) = match upper {
+            true => (m.median - m.height / two, top(m)),
+            false => (bottom(m), m.median + m.height / two),
+        };
+        let mut builder = VertLineBuilder::default();
+        builder
+            .start(Point::new(m.width / two, from))
+            .end(Point::new(m.width / two, to))
+            .stroke(m.stroke);
+        match upper {
+            true => builder.butt_top(m.stroke),
+            false => builder.butt_bot(m.stroke),
+        };
+        builder.build().unwrap().into()
+    });
+    let serif: Configure<F> = Box::new(move |m: &Metrics<F>| {
+        let two = F::one() + F::one();
+        let reach = m.width * F::from(REACH).unwrap() / F::from(8).unwrap();
+        let y = if upper { top(m) } else { bottom(m) };
+        let x = m.width / two;
+        let (left, right) = if right {
+            (x, x + reach)
+        } else {
+            (x - reach, x)
+        };
+        let mut builder = HorLineBuilder::default();
+        builder
+            .start(Point::new(left, y))
+            .end(Point::new(right, y))
+            .stroke(m.stroke)
+            .butt_left(F::zero())
+            .butt_right(F::zero());
+        builder.build().unwrap().into()
+    });
+    vec![stem, serif]
+}
+
+/// The horizontal scan line `line` of 9, 1 at the top of the block area, 5
+/// on the median and 9 at the bottom.
+fn scan_line<F>(line: u32) -> Configure<F>
+where
+    F: Float + AddAssign + SubAssign + 'static,
+{
+    Box::new(move |m: &Metrics<F>| {
+        let f = |v: u32| F::from(v).unwrap();
+        let median = match line {
+            1..=5 => m.median + (top(m) - m.median) * f(5 - line) / f(4),
+            _ => m.median - (m.median - bottom(m)) * f(line - 5) / f(4),
+        };
+        let mut builder = HorBarBuilder::default();
+        builder.median(median);
+        builder.build().unwrap().into()
+    })
+}
+
+/// The bracket piece, integral piece or scan line `c`.
+pub(crate) fn piece<F>(c: u32) -> Recipe<F>
+where
+    F: Float + AddAssign + SubAssign + 'static,
+{
+    let commands = match c {
+        0x2320 | 0x239B | 0x23A7 => vec![arc(Side::TopLeft, top)],
+        0x2321 | 0x23A0 | 0x23AD => vec![arc(Side::BottomRight, bottom)],
+        0x239D | 0x23A9 => vec![arc(Side::BottomLeft, bottom)],
+        0x239E | 0x23AB => vec![arc(Side::TopRight, top)],
+        0x239C | 0x239F | 0x23A2 | 0x23A5 | 0x23AA | 0x23AE => vec![stem()],
+        0x23A1 => corner(true, true),
+        0x23A3 => corner(false, true),
+        0x23A4 => corner(true, false),
+        0x23A6 => corner(false, false),
+        0x23A8 => vec![arc(Side::BottomRight, median), arc(Side::TopRight, median)],
+        0x23AC => vec![arc(Side::BottomLeft, median), arc(Side::TopLeft, median)],
+        0x23BA..=0x23BD => vec![scan_line([1, 3, 7, 9][(c - 0x23BA) as usize])],
+        _ => unreachable!("0x{:X} is not a bracket piece or scan line", c),
+    };
+    let name = match c {
+        0x2320 => "integraltp".into(),
+        0x2321 => "integralbt".into(),
+        0x239B..=0x23AE => NAMES[(c - 0x239B) as usize].into(),
+        _ => format!("uni{:04X}", c),
+    };
+    Recipe::new(c, name, commands)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::drawing_command::Metrics;
+    use crate::font::Font;
+
+    #[test]
+    fn pieces_stack_without_gaps() {
+        let font = Font::new(Metrics::<f64>::default());
+        assert_eq!(Font::<f64>::glyph_name(0x23AE), "integralex");
+
+        // pieces meet the stem of the ones above and below on the cell
+        // edges, and scan line 5 would be the light horizontal line
+        let stem = font.render_cell(0x2502, 10, 20, 1);
+        for (upper, lower) in [(0x239B, 0x239D), (0x23A7, 0x23A8), (0x23A8, 0x23A9)] {
+            let (upper, lower) = (
+                font.render_cell(upper, 10, 20, 1),
+                font.render_cell(lower, 10, 20, 1),
+            );
+            for x in 0..10 {
+                assert_eq!(upper.get(x, 19), stem.get(x, 19));
+                assert_eq!(lower.get(x, 0), stem.get(x, 0));
+            }
+        }
+        let (top, bottom) = (
+            font.render_cell(0x23BA, 10, 20, 1),
+            font.render_cell(0x23BD, 10, 20, 1),
+        );
+        assert!((0..10).all(|x| top.get(x, 0) == 255 && bottom.get(x, 19) == 255));
+    }
+}
@@ -0,0 +1,110 @@
+use std::fmt::Write;
+use std::io;
+
+use num::Float;
+
+use super::plist::escape;
+use super::xml::{self, invalid};
+use crate::drawing_command::Point;
+use crate::outline::{Outline, PathSegment};
+use crate::svg::number;
+
+/// Writes a glyph in the `.glif` format 2 of UFO 3.
+pub(crate) fn glif<F: Float>(
+    name: &str,
+    unicodes: &[u32],
+    advance: F,
+    outline: &Outline<F>,
+) -> String {
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    writeln!(xml, "<glyph name=\"{}\" format=\"2\">", escape(name)).unwrap();
+    writeln!(xml, "  <advance width=\"{}\"/>", number(advance)).unwrap();
+    for unicode in unicodes {
+        writeln!(xml, "  <unicode hex=\"{:04X}\"/>", unicode).unwrap();
+    }
+    if outline.is_empty() {
+        xml.push_str("  <outline/>\n");
+    } else {
+        xml.push_str("  <outline>\n");
+        for contour in outline.contours() {
+            xml.push_str("    <contour>\n");
+            for (pt, kind) in contour_points(contour.segments(), contour.is_closed()) {
+                write!(
+                    xml,
+                    "      <point x=\"{}\" y=\"{}\"",
+                    number(pt.x),
+                    number(pt.y)
+                )
+                .unwrap();
+                if let Some(kind) = kind {
+                    write!(xml, " type=\"{}\"", kind).unwrap();
+                }
+                xml.push_str("/>\n");
+            }
+            xml.push_str("    </contour>\n");
+        }
+        xml.push_str("  </outline>\n");
+    }
+    xml.push_str("</glyph>\n");
+    xml
+}
+
+/// The name, advance width and code points of a `.glif` file.
+pub(crate) fn read_glif(xml: &str) -> io::Result<(String, f64, Vec<u32>)> {
+    let glyph = xml::parse(xml)?;
+    let name = glyph
+        .attribute("name")
+        .ok_or_else(|| invalid("glyph without a name"))?;
+    let advance = match glyph.child("advance").and_then(|a| a.attribute("width")) {
+        Some(width) => width
+            .parse()
+            .map_err(|_| invalid(&format!("invalid advance width of `{}`", name)))?,
+        None => 0.,
+    };
+    let unicodes = glyph
+        .children
+        .iter()
+        .filter(|e| e.name == "unicode")
+        .filter_map(|e| e.attribute("hex"))
+        .map(|hex| {
+            u32::from_str_radix(hex, 16)
+                .map_err(|_| invalid(&format!("invalid unicode of `{}`", name)))
+        })
+        .collect::<io::Result<_>>()?;
+    Ok((name.to_string(), advance, unicodes))
+}
+
+/// The points of a contour with their types, `None` for off-curve points.
+///
+/// A closed contour starts with the end point of its last segment, which
+/// implies the closing line if there is none.
+fn contour_points<F: Float>(
+    segments: &[PathSegment<F>],
+    closed: bool,
+) -> Vec<(Point<F>, Option<&'static str>)> {
+    let mut points = Vec::new();
+    for segment in segments {
+        match *segment {
+            PathSegment::Move(pt) => points.push((pt, Some("move"))),
+            PathSegment::Line(pt) => points.push((pt, Some("line"))),
+            PathSegment::Cubic(pt1, pt2, end) => {
+                points.push((pt1, None));
+                points.push((pt2, None));
+                points.push((end, Some("curve")));
+            }
+            PathSegment::Quad(pt1, end) => {
+                points.push((pt1, None));
+                points.push((end, Some("qcurve")));
+            }
+            PathSegment::Close => {}
+        }
+    }
+    if closed {
+        points[0].1 = Some("line");
+        if points.len() > 1 && points.last().map(|p| p.0) == Some(points[0].0) {
+            let (_, kind) = points.pop().unwrap();
+            points[0].1 = kind;
+        }
+    }
+    points
+}
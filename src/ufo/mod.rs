@@ -0,0 +1,496 @@
+//! Font sources in the Unified Font Object format, version 3.
+
+mod glif;
+mod plist;
+mod xml;
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::ops::{AddAssign, SubAssign};
+use std::path::{Path, PathBuf};
+
+use num::Float;
+
+use crate::drawing_command::Metrics;
+use crate::font::{CodePointError, Font};
+use plist::Value;
+use xml::invalid;
+
+/// Writes the glyphs of a [`Font`] into a new UFO 3 source.
+///
+/// Glyphs are written for the chosen code points, by default
+/// [`Font::default_code_points`]. Each glyph carries its recipe name, its
+/// code point and `Metrics.width` as advance. Outlines are written as drawn, with PostScript contour
+/// direction, so they are left for a font editor to clean up.
+pub struct UfoWriter<'a, F: Float> {
+    font: &'a Font<F>,
+    family_name: String,
+    units_per_em: u16,
+    code_points: Vec<u32>,
+}
+
+impl<'a, F> UfoWriter<'a, F>
+where
+    F: Float + Default + AddAssign + SubAssign + 'static,
+{
+    /// A writer for a font of the given family name and 1000 units per em.
+    pub fn new(font: &'a Font<F>, family_name: &str) -> UfoWriter<'a, F> {
+        UfoWriter {
+            font,
+            family_name: family_name.to_string(),
+            units_per_em: 1000,
+            code_points: font.default_code_points().collect(),
+        }
+    }
+
+    /// The code points to include, all with a glyph in the font.
+    pub fn code_points(
+        mut self,
+        code_points: impl IntoIterator<Item = u32>,
+    ) -> Result<Self, CodePointError> {
+        self.code_points = self.font.select(code_points)?;
+        Ok(self)
+    }
+
+    /// Sets how many font units the em square holds, without scaling the
+    /// glyphs.
+    pub fn units_per_em(mut self, units_per_em: u16) -> Self {
+        self.units_per_em = units_per_em;
+        self
+    }
+
+    /// Creates the UFO directory at `path`, which must not exist yet.
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let path = path.as_ref();
+        let metrics = self.font.metrics();
+        fs::create_dir(path)?;
+        fs::create_dir(path.join("glyphs"))?;
+
+        let metainfo = Value::Dictionary(vec![
+            ("creator".into(), Value::String("box-drawing".into())),
+            ("formatVersion".into(), Value::Integer(3)),
+        ]);
+        fs::write(path.join("metainfo.plist"), metainfo.to_xml())?;
+
+        let fontinfo = Value::Dictionary(vec![
+            ("familyName".into(), Value::String(self.family_name.clone())),
+            ("styleName".into(), Value::String("Regular".into())),
+            ("versionMajor".into(), Value::Integer(1)),
+            ("versionMinor".into(), Value::Integer(0)),
+            (
+                "unitsPerEm".into(),
+                Value::Integer(self.units_per_em.into()),
+            ),
+            ("ascender".into(), number(metrics.block_top.y)),
+            ("descender".into(), number(metrics.block_origin.y)),
+            ("postscriptIsFixedPitch".into(), Value::Boolean(true)),
+        ]);
+        fs::write(path.join("fontinfo.plist"), fontinfo.to_xml())?;
+
+        let layercontents = Value::Array(vec![Value::Array(vec![
+            Value::String("public.default".into()),
+            Value::String("glyphs".into()),
+        ])]);
+        fs::write(path.join("layercontents.plist"), layercontents.to_xml())?;
+
+        let mut taken = HashSet::new();
+        let mut contents = Vec::new();
+        let mut order = Vec::new();
+        for c in self.code_points.iter().copied() {
+            let name = Font::<F>::glyph_name(c);
+            let file_name = glyph_file_name(&name, &taken);
+            taken.insert(file_name.to_lowercase());
+
+            let glif = glyph_glif(self.font, c, &name, &[c]);
+            fs::write(path.join("glyphs").join(&file_name), glif)?;
+            contents.push((name.to_string(), Value::String(file_name)));
+            order.push(Value::String(name.to_string()));
+        }
+        fs::write(
+            path.join("glyphs").join("contents.plist"),
+            Value::Dictionary(contents).to_xml(),
+        )?;
+
+        let lib = Value::Dictionary(vec![("public.glyphOrder".into(), Value::Array(order))]);
+        fs::write(path.join("lib.plist"), lib.to_xml())
+    }
+}
+
+/// What [`Ufo::merge`] and [`FontPatcher`](crate::sfnt::FontPatcher) do with
+/// glyphs the font already has.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MergePolicy {
+    /// Draws them anew, keeping their names and places.
+    Replace,
+    /// Leaves them as they are.
+    Keep,
+}
+
+/// Code points handled by a merge, by what happened to them.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MergeReport {
+    pub added: Vec<u32>,
+    pub replaced: Vec<u32>,
+    pub kept: Vec<u32>,
+}
+
+struct GlyphEntry {
+    name: String,
+    file_name: String,
+    advance: f64,
+    unicodes: Vec<u32>,
+}
+
+/// An existing UFO 3 source, to add the generated glyphs to.
+pub struct Ufo {
+    path: PathBuf,
+    fontinfo: Value,
+    /// Directory of the default layer.
+    layer: PathBuf,
+    glyphs: Vec<GlyphEntry>,
+}
+
+impl Ufo {
+    /// Reads the font info and the names, advance widths and code points of
+    /// all glyphs in the default layer.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Ufo> {
+        let path = path.as_ref().to_path_buf();
+        let fontinfo = match fs::read_to_string(path.join("fontinfo.plist")) {
+            Ok(xml) => Value::parse(&xml)?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Value::Dictionary(Vec::new()),
+            Err(e) => return Err(e),
+        };
+
+        let mut layer = String::from("glyphs");
+        if let Ok(xml) = fs::read_to_string(path.join("layercontents.plist")) {
+            if let Value::Array(layers) = Value::parse(&xml)? {
+                for entry in layers {
+                    if let Value::Array(pair) = entry {
+                        if let [Value::String(name), Value::String(dir)] = pair.as_slice() {
+                            if name == "public.default" {
+                                layer = dir.clone();
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        let layer = path.join(layer);
+
+        let contents = Value::parse(&fs::read_to_string(layer.join("contents.plist"))?)?;
+        let entries = match contents {
+            Value::Dictionary(entries) => entries,
+            _ => return Err(invalid("contents.plist is not a dictionary")),
+        };
+        let mut glyphs = Vec::new();
+        for (name, file_name) in entries {
+            let file_name = file_name
+                .as_str()
+                .ok_or_else(|| invalid(&format!("no file name for `{}`", name)))?
+                .to_string();
+            let (_, advance, unicodes) =
+                glif::read_glif(&fs::read_to_string(layer.join(&file_name))?)?;
+            glyphs.push(GlyphEntry {
+                name,
+                file_name,
+                advance,
+                unicodes,
+            });
+        }
+
+        Ok(Ufo {
+            path,
+            fontinfo,
+            layer,
+            glyphs,
+        })
+    }
+
+    /// Metrics matching the font: the most common advance width as glyph
+    /// width, and the line extents from the font info.
+    ///
+    /// The hhea ascender and descender are preferred over the typographic
+    /// ones, which are preferred over the general ones.
+    pub fn metrics<F: Float>(&self) -> io::Result<Metrics<F>> {
+        let mut counts: HashMap<u64, usize> = HashMap::new();
+        for glyph in self.glyphs.iter().filter(|g| g.advance > 0.) {
+            *counts.entry(glyph.advance.to_bits()).or_default() += 1;
+        }
+        let width = counts
+            .into_iter()
+            .max_by_key(|(bits, count)| (*count, std::cmp::Reverse(*bits)))
+            .map(|(bits, _)| f64::from_bits(bits))
+            .ok_or_else(|| invalid("no glyph has an advance width"))?;
+
+        let info = |keys: [&str; 3]| {
+            keys.iter()
+                .find_map(|key| self.fontinfo.get(key).and_then(Value::as_f64))
+                .ok_or_else(|| invalid(&format!("fontinfo.plist has no `{}`", keys[2])))
+        };
+        let ascender = info([
+            "openTypeHheaAscender",
+            "openTypeOS2TypoAscender",
+            "ascender",
+        ])?;
+        let descender = info([
+            "openTypeHheaDescender",
+            "openTypeOS2TypoDescender",
+            "descender",
+        ])?;
+
+        let f = |v: f64| F::from(v).unwrap();
+        Ok(Metrics::for_cell(f(width), f(ascender), f(descender)))
+    }
+
+    /// Writes a glyph of `font` for each of `code_points`, e.g.
+    /// [`Font::default_code_points`].
+    ///
+    /// A glyph the source already has, found by code point or else by name,
+    /// is replaced or kept according to `policy`. New glyphs are added to
+    /// `contents.plist` and to the glyph order; all other glyphs and entries
+    /// are left alone. Fails with [`io::ErrorKind::InvalidInput`] before
+    /// writing anything if `font` has no glyph for one of `code_points`.
+    pub fn merge<F>(
+        &mut self,
+        font: &Font<F>,
+        code_points: impl IntoIterator<Item = u32>,
+        policy: MergePolicy,
+    ) -> io::Result<MergeReport>
+    where
+        F: Float + Default + AddAssign + SubAssign + 'static,
+    {
+        let code_points = font
+            .select(code_points)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        let mut report = MergeReport::default();
+        let mut added = Vec::new();
+        let mut taken: HashSet<String> = self
+            .glyphs
+            .iter()
+            .map(|g| g.file_name.to_lowercase())
+            .collect();
+
+        for c in code_points {
+            let name = Font::<F>::glyph_name(c);
+            let existing = self
+                .glyphs
+                .iter()
+                .position(|g| g.unicodes.contains(&c))
+                .or_else(|| self.glyphs.iter().position(|g| g.name == name));
+            match (existing, policy) {
+                (Some(_), MergePolicy::Keep) => report.kept.push(c),
+                (Some(i), MergePolicy::Replace) => {
+                    let glyph = &mut self.glyphs[i];
+                    if !glyph.unicodes.contains(&c) {
+                        glyph.unicodes.push(c);
+                    }
+                    glyph.advance = font.metrics().width.to_f64().unwrap();
+                    let glif = glyph_glif(font, c, &glyph.name, &glyph.unicodes);
+                    fs::write(self.layer.join(&glyph.file_name), glif)?;
+                    report.replaced.push(c);
+                }
+                (None, _) => {
+                    let file_name = glyph_file_name(&name, &taken);
+                    taken.insert(file_name.to_lowercase());
+                    fs::write(
+                        self.layer.join(&file_name),
+                        glyph_glif(font, c, &name, &[c]),
+                    )?;
+                    self.glyphs.push(GlyphEntry {
+                        name: name.to_string(),
+                        file_name,
+                        advance: font.metrics().width.to_f64().unwrap(),
+                        unicodes: vec![c],
+                    });
+                    added.push(name);
+                    report.added.push(c);
+                }
+            }
+        }
+        if added.is_empty() {
+            return Ok(report);
+        }
+
+        // Re-read the files, so entries unknown to us survive.
+        let contents_path = self.layer.join("contents.plist");
+        let mut contents = Value::parse(&fs::read_to_string(&contents_path)?)?;
+        if let Value::Dictionary(entries) = &mut contents {
+            let new = &self.glyphs[self.glyphs.len() - added.len()..];
+            entries.extend(
+                new.iter()
+                    .map(|g| (g.name.clone(), Value::String(g.file_name.clone()))),
+            );
+        }
+        fs::write(&contents_path, contents.to_xml())?;
+
+        let lib_path = self.path.join("lib.plist");
+        if let Ok(xml) = fs::read_to_string(&lib_path) {
+            let mut lib = Value::parse(&xml)?;
+            if let Value::Dictionary(entries) = &mut lib {
+                for (key, value) in entries.iter_mut() {
+                    if let (true, Value::Array(order)) = (key == "public.glyphOrder", value) {
+                        order.extend(added.iter().map(|name| Value::String(name.to_string())));
+                    }
+                }
+            }
+            fs::write(&lib_path, lib.to_xml())?;
+        }
+        Ok(report)
+    }
+}
+
+/// The `.glif` of a generated glyph, with PostScript contour direction.
+fn glyph_glif<F>(font: &Font<F>, c: u32, name: &str, unicodes: &[u32]) -> String
+where
+    F: Float + Default + AddAssign + SubAssign + 'static,
+{
+    let outline = font.outline(c).correct_direction(false);
+    glif::glif(name, unicodes, font.metrics().width, &outline)
+}
+
+fn number<F: Float>(v: F) -> Value {
+    let v = v.to_f64().unwrap();
+    if v == v.round() {
+        Value::Integer(v as i64)
+    } else {
+        Value::Real(v)
+    }
+}
+
+/// File name of a glyph, following the user name to file name convention
+/// of UFO 3. `taken` holds the lowercased names already in use.
+pub(crate) fn glyph_file_name(name: &str, taken: &HashSet<String>) -> String {
+    const ILLEGAL: &str = "\"*+/:<>?[\\]|";
+    const RESERVED: [&str; 5] = ["con", "prn", "aux", "clock$", "nul"];
+
+    let mut stem = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if (i == 0 && c == '.') || c < ' ' || c == '\x7f' || ILLEGAL.contains(c) {
+            stem.push('_');
+        } else if c.is_uppercase() {
+            stem.push(c);
+            stem.push('_');
+        } else {
+            stem.push(c);
+        }
+    }
+    let stem = stem
+        .split('.')
+        .map(|part| {
+            let lower = part.to_lowercase();
+            let numbered = |prefix: &str| {
+                lower.len() == 4
+                    && lower.starts_with(prefix)
+                    && matches!(lower.as_bytes()[3], b'1'..=b'9')
+            };
+            if RESERVED.contains(&lower.as_str()) || numbered("com") || numbered("lpt") {
+                format!("_{}", part)
+            } else {
+                part.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(".");
+    let stem: String = stem.chars().take(255 - ".glif".len() - 15).collect();
+
+    let file_name = format!("{}.glif", stem);
+    if !taken.contains(&file_name.to_lowercase()) {
+        return file_name;
+    }
+    (1..)
+        .map(|i| format!("{}{:015}.glif", stem, i))
+        .find(|file_name| !taken.contains(&file_name.to_lowercase()))
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::outline::Outline;
+
+    #[test]
+    fn file_names() {
+        let mut taken = HashSet::new();
+        assert_eq!(glyph_file_name("lighthorzbxd", &taken), "lighthorzbxd.glif");
+        assert_eq!(glyph_file_name("A", &taken), "A_.glif");
+        assert_eq!(glyph_file_name(".notdef", &taken), "_notdef.glif");
+        assert_eq!(glyph_file_name("con.alt", &taken), "_con.alt.glif");
+        taken.insert(String::from("a_.glif"));
+        assert_eq!(glyph_file_name("A", &taken), "A_000000000000001.glif");
+    }
+
+    #[test]
+    fn writes_the_chosen_glyphs() {
+        let path = std::env::temp_dir().join(format!("box-drawing-{}.ufo", std::process::id()));
+        let _ = fs::remove_dir_all(&path);
+        let font = Font::new(Metrics::<f64>::default());
+        UfoWriter::new(&font, "Box Drawing")
+            .code_points(0x2500..=0x257F)
+            .unwrap()
+            .write(&path)
+            .unwrap();
+
+        let glif = fs::read_to_string(path.join("glyphs").join("lighthorzbxd.glif")).unwrap();
+        let glyphs = fs::read_dir(path.join("glyphs")).unwrap().count();
+        fs::remove_dir_all(&path).unwrap();
+
+        assert_eq!(glyphs, 0x80 + 1);
+        assert!(glif.contains("<glyph name=\"lighthorzbxd\" format=\"2\">"));
+        assert!(glif.contains("<advance width=\"600\"/>"));
+        assert!(glif.contains("<unicode hex=\"2500\"/>"));
+    }
+
+    #[test]
+    fn merges_into_existing_source() {
+        let path =
+            std::env::temp_dir().join(format!("box-drawing-merge-{}.ufo", std::process::id()));
+        let _ = fs::remove_dir_all(&path);
+        let info = Value::Dictionary(vec![
+            ("ascender".into(), Value::Integer(800)),
+            ("descender".into(), Value::Integer(-200)),
+        ]);
+        let contents = Value::Dictionary(vec![
+            ("zero".into(), Value::String("zero.glif".into())),
+            ("SF100000".into(), Value::String("S_F_100000.glif".into())),
+        ]);
+        let zero = glif::glif::<f64>("zero", &[0x30], 500., &Outline::new());
+        fs::create_dir_all(path.join("glyphs")).unwrap();
+        fs::write(path.join("fontinfo.plist"), info.to_xml()).unwrap();
+        fs::write(path.join("glyphs/contents.plist"), contents.to_xml()).unwrap();
+        fs::write(path.join("glyphs/zero.glif"), &zero).unwrap();
+        fs::write(
+            path.join("glyphs/S_F_100000.glif"),
+            glif::glif::<f64>("SF100000", &[0x2500], 500., &Outline::new()),
+        )
+        .unwrap();
+
+        let mut ufo = Ufo::open(&path).unwrap();
+        let metrics = ufo.metrics::<f64>().unwrap();
+        assert_eq!((metrics.width, metrics.block_height), (500., 1000.));
+        let font = Font::new(metrics);
+        let error = ufo.merge(&font, [0xE0B0], MergePolicy::Keep).err().unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        let report = ufo
+            .merge(&font, font.default_code_points(), MergePolicy::Keep)
+            .unwrap();
+
+        let contents =
+            Value::parse(&fs::read_to_string(path.join("glyphs/contents.plist")).unwrap());
+        let kept = fs::read_to_string(path.join("glyphs/zero.glif")).unwrap();
+        fs::remove_dir_all(&path).unwrap();
+
+        assert_eq!(report.kept, vec![0x2500]);
+        assert_eq!(report.added.len(), 0xA0 - 1);
+        assert_eq!(kept, zero);
+        let contents = contents.unwrap();
+        assert_eq!(
+            contents.get("SF100000").and_then(Value::as_str),
+            Some("S_F_100000.glif")
+        );
+        assert_eq!(
+            contents.get("lightvertbxd").and_then(Value::as_str),
+            Some("lightvertbxd.glif")
+        );
+    }
+}
@@ -0,0 +1,156 @@
+use std::fmt::Write;
+use std::io;
+
+use super::xml::{self, invalid, Element};
+
+/// A value of an XML property list.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Value {
+    String(String),
+    Integer(i64),
+    Real(f64),
+    Boolean(bool),
+    /// Base64 data and dates are kept as written.
+    Data(String),
+    Date(String),
+    Array(Vec<Value>),
+    /// Keys and values, in file order.
+    Dictionary(Vec<(String, Value)>),
+}
+
+impl Value {
+    /// Reads a property list document.
+    pub fn parse(xml: &str) -> io::Result<Value> {
+        let root = xml::parse(xml)?;
+        match root.children.as_slice() {
+            [value] if root.name == "plist" => Value::from_element(value),
+            _ => Err(invalid("not a property list")),
+        }
+    }
+
+    fn from_element(element: &Element) -> io::Result<Value> {
+        let number = || invalid(&format!("invalid number `{}`", element.text));
+        Ok(match element.name.as_str() {
+            "string" => Value::String(element.text.clone()),
+            "integer" => Value::Integer(element.text.trim().parse().map_err(|_| number())?),
+            "real" => Value::Real(element.text.trim().parse().map_err(|_| number())?),
+            "true" => Value::Boolean(true),
+            "false" => Value::Boolean(false),
+            "data" => Value::Data(element.text.clone()),
+            "date" => Value::Date(element.text.clone()),
+            "array" => Value::Array(
+                element
+                    .children
+                    .iter()
+                    .map(Value::from_element)
+                    .collect::<io::Result<_>>()?,
+            ),
+            "dict" => {
+                let mut entries = Vec::new();
+                for pair in element.children.chunks(2) {
+                    match pair {
+                        [key, value] if key.name == "key" => {
+                            entries.push((key.text.clone(), Value::from_element(value)?))
+                        }
+                        _ => return Err(invalid("dictionary keys and values do not pair up")),
+                    }
+                }
+                Value::Dictionary(entries)
+            }
+            name => {
+                return Err(invalid(&format!(
+                    "unknown property list element `{}`",
+                    name
+                )))
+            }
+        })
+    }
+
+    /// Looks up a key of a dictionary.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Dictionary(entries) => entries.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Integers and reals alike.
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Value::Integer(v) => Some(v as f64),
+            Value::Real(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// The whole property list document.
+    pub fn to_xml(&self) -> String {
+        let mut xml = String::from(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" \
+             \"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">\n\
+             <plist version=\"1.0\">\n",
+        );
+        self.write(&mut xml, 0);
+        xml.push_str("</plist>\n");
+        xml
+    }
+
+    fn write(&self, xml: &mut String, depth: usize) {
+        let indent = "  ".repeat(depth);
+        match self {
+            Value::String(s) => writeln!(xml, "{}<string>{}</string>", indent, escape(s)),
+            Value::Integer(v) => writeln!(xml, "{}<integer>{}</integer>", indent, v),
+            Value::Real(v) => writeln!(xml, "{}<real>{}</real>", indent, v),
+            Value::Boolean(v) => writeln!(xml, "{}<{}/>", indent, v),
+            Value::Data(v) => writeln!(xml, "{}<data>{}</data>", indent, escape(v)),
+            Value::Date(v) => writeln!(xml, "{}<date>{}</date>", indent, escape(v)),
+            Value::Array(values) => {
+                writeln!(xml, "{}<array>", indent).unwrap();
+                values.iter().for_each(|v| v.write(xml, depth + 1));
+                writeln!(xml, "{}</array>", indent)
+            }
+            Value::Dictionary(entries) => {
+                writeln!(xml, "{}<dict>", indent).unwrap();
+                for (key, value) in entries {
+                    writeln!(xml, "{}  <key>{}</key>", indent, escape(key)).unwrap();
+                    value.write(xml, depth + 1);
+                }
+                writeln!(xml, "{}</dict>", indent)
+            }
+        }
+        .unwrap();
+    }
+}
+
+/// Escapes text for XML content and attribute values.
+pub(crate) fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let value = Value::Dictionary(vec![
+            ("name".into(), Value::String("a < b".into())),
+            ("size".into(), Value::Real(0.5)),
+            ("fixed".into(), Value::Boolean(true)),
+            ("order".into(), Value::Array(vec![Value::Integer(-3)])),
+            ("empty".into(), Value::Dictionary(Vec::new())),
+        ]);
+        assert_eq!(Value::parse(&value.to_xml()).unwrap(), value);
+    }
+}
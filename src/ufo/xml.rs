@@ -0,0 +1,203 @@
+//! Just enough of an XML reader for property lists and `.glif` files.
+
+use std::io;
+
+/// An element with its attributes, child elements and text content.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct Element {
+    pub name: String,
+    pub attributes: Vec<(String, String)>,
+    pub children: Vec<Element>,
+    pub text: String,
+}
+
+impl Element {
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn child(&self, name: &str) -> Option<&Element> {
+        self.children.iter().find(|e| e.name == name)
+    }
+}
+
+pub(crate) fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+/// Parses a document into its root element.
+pub(crate) fn parse(xml: &str) -> io::Result<Element> {
+    let mut parser = Parser { rest: xml };
+    parser.skip_misc()?;
+    let root = parser.element()?;
+    parser.skip_misc()?;
+    if !parser.rest.is_empty() {
+        return Err(invalid("trailing content after the root element"));
+    }
+    Ok(root)
+}
+
+struct Parser<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Parser<'a> {
+    /// Skips whitespace, the XML declaration, processing instructions,
+    /// comments and the doctype.
+    fn skip_misc(&mut self) -> io::Result<()> {
+        loop {
+            self.rest = self.rest.trim_start();
+            let end = if self.rest.starts_with("<?") {
+                "?>"
+            } else if self.rest.starts_with("<!--") {
+                "-->"
+            } else if self.rest.starts_with("<!") {
+                ">"
+            } else {
+                return Ok(());
+            };
+            self.skip_past(end)?;
+        }
+    }
+
+    fn skip_past(&mut self, end: &str) -> io::Result<&'a str> {
+        let at = self
+            .rest
+            .find(end)
+            .ok_or_else(|| invalid(&format!("missing `{}`", end)))?;
+        let skipped = &self.rest[..at];
+        self.rest = &self.rest[at + end.len()..];
+        Ok(skipped)
+    }
+
+    fn name(&mut self) -> io::Result<String> {
+        let end = self
+            .rest
+            .find(|c: char| c.is_whitespace() || c == '/' || c == '>' || c == '=')
+            .unwrap_or(self.rest.len());
+        if end == 0 {
+            return Err(invalid("expected a name"));
+        }
+        let name = self.rest[..end].to_string();
+        self.rest = &self.rest[end..];
+        Ok(name)
+    }
+
+    fn element(&mut self) -> io::Result<Element> {
+        if !self.rest.starts_with('<') {
+            return Err(invalid("expected an element"));
+        }
+        self.rest = &self.rest[1..];
+        let mut element = Element {
+            name: self.name()?,
+            ..Element::default()
+        };
+
+        loop {
+            self.rest = self.rest.trim_start();
+            if let Some(rest) = self.rest.strip_prefix("/>") {
+                self.rest = rest;
+                return Ok(element);
+            }
+            if let Some(rest) = self.rest.strip_prefix('>') {
+                self.rest = rest;
+                break;
+            }
+            let name = self.name()?;
+            self.rest = self.rest.trim_start();
+            self.rest = self
+                .rest
+                .strip_prefix('=')
+                .ok_or_else(|| invalid("expected `=`"))?
+                .trim_start();
+            let quote = self
+                .rest
+                .chars()
+                .next()
+                .filter(|c| *c == '"' || *c == '\'')
+                .ok_or_else(|| invalid("expected a quoted value"))?;
+            self.rest = &self.rest[1..];
+            let value = self.skip_past(&quote.to_string())?;
+            element.attributes.push((name, unescape(value)?));
+        }
+
+        loop {
+            if let Some(rest) = self.rest.strip_prefix("</") {
+                self.rest = rest;
+                let name = self.name()?;
+                if name != element.name {
+                    return Err(invalid(&format!("`{}` closed by `{}`", element.name, name)));
+                }
+                self.skip_past(">")?;
+                return Ok(element);
+            } else if self.rest.starts_with("<!--") {
+                self.skip_past("-->")?;
+            } else if let Some(rest) = self.rest.strip_prefix("<![CDATA[") {
+                self.rest = rest;
+                let text = self.skip_past("]]>")?;
+                element.text.push_str(text);
+            } else if self.rest.starts_with('<') {
+                element.children.push(self.element()?);
+            } else if self.rest.is_empty() {
+                return Err(invalid(&format!("`{}` is not closed", element.name)));
+            } else {
+                let end = self.rest.find('<').unwrap_or(self.rest.len());
+                element.text.push_str(&unescape(&self.rest[..end])?);
+                self.rest = &self.rest[end..];
+            }
+        }
+    }
+}
+
+fn unescape(text: &str) -> io::Result<String> {
+    let mut unescaped = String::new();
+    let mut rest = text;
+    while let Some(at) = rest.find('&') {
+        unescaped.push_str(&rest[..at]);
+        let end = rest[at..]
+            .find(';')
+            .ok_or_else(|| invalid("unterminated entity"))?;
+        let entity = &rest[at + 1..at + end];
+        let c = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => match entity
+                .strip_prefix("#x")
+                .or_else(|| entity.strip_prefix("#X"))
+            {
+                Some(hex) => u32::from_str_radix(hex, 16).ok().and_then(char::from_u32),
+                None => entity
+                    .strip_prefix('#')
+                    .and_then(|dec| dec.parse().ok())
+                    .and_then(char::from_u32),
+            },
+        };
+        unescaped.push(c.ok_or_else(|| invalid(&format!("unknown entity `{}`", entity)))?);
+        rest = &rest[at + end + 1..];
+    }
+    unescaped.push_str(rest);
+    Ok(unescaped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn elements_and_entities() {
+        let root = parse(
+            "<?xml version=\"1.0\"?>\n<!DOCTYPE x>\n<a k='1 &amp; 2'><!-- note --><b/>x &lt; y</a>",
+        )
+        .unwrap();
+        assert_eq!(root.attribute("k"), Some("1 & 2"));
+        assert_eq!(root.children.len(), 1);
+        assert_eq!(root.text, "x < y");
+        assert!(parse("<a><b></a>").is_err());
+    }
+}